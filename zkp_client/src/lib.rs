@@ -11,4 +11,5 @@ pub enum ZkpClientAuthenticationStatus {
     UnregisteredUser,
 }
 
+pub mod rest;
 pub mod zkp_auth_client;
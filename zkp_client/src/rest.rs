@@ -0,0 +1,195 @@
+//! The external REST interface of the ZKP Client - the users register and log in with their
+//! user name and password, and the client runs the ZKP protocol with the Auth Server for them.
+
+/// External REST endpoints for the ZKP Client
+pub mod filters {
+    use super::handlers;
+    use super::models::LoginDetails;
+    use warp::Filter;
+
+    pub fn ext_clients(
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        register().or(login())
+    }
+
+    /// POST /register with expected payload, { user : String, password: String  }
+    pub fn register() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone
+    {
+        warp::path!("register")
+            .and(warp::post())
+            .and(json_body())
+            .and_then(handlers::handle_registration)
+    }
+
+    /// POST /login with expected payload, { user: String, password: String }
+    pub fn login() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+        warp::path!("login")
+            .and(warp::post())
+            .and(json_body())
+            .and_then(handlers::handle_login)
+    }
+
+    fn json_body() -> impl Filter<Extract = (LoginDetails,), Error = warp::Rejection> + Clone {
+        warp::body::json()
+    }
+}
+
+/// Handlers for the external REST endpoints
+pub mod handlers {
+    use super::models::{AuthenticationResponse, LoginDetails, RegistrationResponse};
+    use crate::{zkp_auth_client, ZkpClientAuthenticationStatus, ZkpClientRegistrationStatus};
+    use std::convert::Infallible;
+    use tonic::Code;
+    use tracing::{debug, error, info};
+    use warp::{http::StatusCode, reply};
+
+    /// The status code of the reply, and the reason, for an error of the ZKP Auth Client - the
+    /// rejections of the Auth Server keep their meaning, and an unreachable server is a 503
+    fn failure(err: Box<dyn std::error::Error>) -> (StatusCode, String) {
+        let Some(status) = err.downcast_ref::<tonic::Status>() else {
+            return if err.is::<tonic::transport::Error>() {
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "the Auth Server is unreachable".into(),
+                )
+            } else {
+                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+            };
+        };
+
+        let code = match status.code() {
+            Code::InvalidArgument | Code::OutOfRange | Code::FailedPrecondition => {
+                StatusCode::BAD_REQUEST
+            }
+            Code::NotFound => StatusCode::NOT_FOUND,
+            Code::AlreadyExists => StatusCode::CONFLICT,
+            Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+            Code::PermissionDenied => StatusCode::FORBIDDEN,
+            Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+            Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::BAD_GATEWAY,
+        };
+
+        (code, status.message().to_string())
+    }
+
+    /// Register the user with the Auth Server via the ZKP Auth client
+    pub async fn handle_registration(login: LoginDetails) -> Result<impl warp::Reply, Infallible> {
+        info!("Registering user {:?} with the server", login.user);
+        debug!("Registration payload: {login:?}");
+
+        Ok(
+            match zkp_auth_client::register(login.user.clone(), login.password).await {
+                Ok(ZkpClientRegistrationStatus::Registered) => reply::with_status(
+                    reply::json(&RegistrationResponse {
+                        user: login.user,
+                        status: "registered".into(),
+                    }),
+                    StatusCode::CREATED,
+                ),
+
+                Ok(ZkpClientRegistrationStatus::AlreadyRegistered) => reply::with_status(
+                    reply::json(&RegistrationResponse {
+                        user: login.user,
+                        status: "already registered".into(),
+                    }),
+                    StatusCode::CONFLICT,
+                ),
+
+                Err(err) => {
+                    error!("Failed to register user {:?}: {err}", login.user);
+
+                    let (code, reason) = failure(err);
+                    reply::with_status(
+                        reply::json(&RegistrationResponse {
+                            user: login.user,
+                            status: format!("not registered - {reason}"),
+                        }),
+                        code,
+                    )
+                }
+            },
+        )
+    }
+
+    /// Attempt to log onto the Auth Server via the ZKP Auth Client
+    pub async fn handle_login(login: LoginDetails) -> Result<impl warp::Reply, Infallible> {
+        info!("Attempting to log user {:?} in", login.user);
+        debug!("Login payload: {login:?}");
+
+        Ok(
+            match zkp_auth_client::login(login.user.clone(), login.password).await {
+                Ok(ZkpClientAuthenticationStatus::UnregisteredUser) => reply::with_status(
+                    reply::json(&AuthenticationResponse {
+                        user: login.user,
+                        status: "unregistered user".into(),
+                        session_id: None,
+                    }),
+                    StatusCode::NOT_FOUND,
+                ),
+                Ok(ZkpClientAuthenticationStatus::Authenticated { session_id }) => {
+                    reply::with_status(
+                        reply::json(&AuthenticationResponse {
+                            user: login.user,
+                            status: "authenticated".into(),
+                            session_id: Some(session_id),
+                        }),
+                        StatusCode::OK,
+                    )
+                }
+                Ok(ZkpClientAuthenticationStatus::NotAuthenticated { status }) => {
+                    reply::with_status(
+                        reply::json(&AuthenticationResponse {
+                            user: login.user,
+                            status: format!("not authenticated - {status}"),
+                            session_id: None,
+                        }),
+                        StatusCode::UNAUTHORIZED,
+                    )
+                }
+
+                Err(err) => {
+                    error!("Failed to log user {:?} in: {err}", login.user);
+
+                    let (code, reason) = failure(err);
+                    reply::with_status(
+                        reply::json(&AuthenticationResponse {
+                            user: login.user,
+                            status: format!("not authenticated - {reason}"),
+                            session_id: None,
+                        }),
+                        code,
+                    )
+                }
+            },
+        )
+    }
+}
+
+/// A simple model for the putative user
+pub mod models {
+    use serde::{Deserialize, Serialize};
+    use zkp_core::Secret;
+
+    #[derive(Debug, Deserialize, Clone)]
+    pub struct LoginDetails {
+        pub user: String,
+        // any UTF-8 string - the secret is derived from it with a KDF (it is wiped once the
+        // request has been handled, and redacted from the logs)
+        pub password: Secret<String>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    pub struct RegistrationResponse {
+        pub user: String,
+        pub status: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct AuthenticationResponse {
+        pub user: String,
+        pub status: String,
+        pub session_id: Option<String>,
+    }
+}
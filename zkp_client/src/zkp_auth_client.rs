@@ -3,110 +3,135 @@ use tracing::{debug, info};
 use lazy_static::lazy_static;
 use std::collections::HashSet;
 use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use num_bigint::BigInt;
 use tonic::transport::Channel;
+use zkp_core::kdf::{self, password_to_secret};
+use zkp_core::{
+    params, transcript, Flavor, Group, KdfParams, ModPGroup, NonceGeneration, Prover,
+    Ristretto255Group, Secret, Transcript,
+};
 
 use zkp_auth::auth_client::AuthClient;
-use zkp_auth::{AuthenticationAnswerRequest, AuthenticationChallengeRequest, RegisterRequest};
+use zkp_auth::{
+    AuthenticationAnswerRequest, AuthenticationAnswerResponse, AuthenticationChallengeRequest,
+    LoginParametersRequest, LoginWithProofRequest, RegisterRequest,
+};
 
 use crate::{ZkpClientAuthenticationStatus, ZkpClientRegistrationStatus};
 
+/// The salt and the KDF parameters chosen at the registration of a user (and stored by the Auth
+/// Server, which returns them when the user logs in)
+#[derive(Debug, Clone)]
+struct Registration {
+    salt: Vec<u8>,
+    kdf: KdfParams,
+}
+
+impl Registration {
+    /// The registration, from the hex-encoded salt and the KDF parameters sent by the Auth Server
+    /// (which are only accepted within bounds, see [`KdfParams::check_bounds`])
+    fn decode(
+        salt: &str,
+        kdf: Option<zkp_auth::KdfParams>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let kdf = KdfParams::from(kdf.ok_or("the response is missing the KDF parameters")?);
+        kdf.check_bounds()?;
+
+        Ok(Registration {
+            salt: hex::decode(salt)?,
+            kdf,
+        })
+    }
+}
+
 // The Auth Client state - a hashset of the users currently registered with the system.
 // In production, this would be a database instead.
 lazy_static! {
-    /// Maintains a set of the users registered via the client - this helps with fast
-    /// lookups to check if the user has been registered or not.
+    /// Maintains the users registered via the client - this helps with fast lookups to check if
+    /// the user has been registered or not. Logging in does not depend on it, so users
+    /// registered by another client (or before a restart) can log in too.
     static ref REGISTERED_USERS: Mutex<HashSet<String>> = {
-        let m = Mutex::new(HashSet::new());
-        m
+        Mutex::new(HashSet::new())
     };
-}
-
-/// The ZKP Chaum-Pedersen Prover
-mod zkp_prover {
-    use num_bigint::{BigInt, RandomBits};
-    use num_traits::Signed;
-    use once_cell::sync::OnceCell;
-    use rand::Rng;
-    use tracing::debug;
 
-    // p, g, and h according to the Chaum-Pedersen protocl (as pert Smart)
-    static P: OnceCell<BigInt> = OnceCell::new();
-    static G: OnceCell<BigInt> = OnceCell::new();
-    static H: OnceCell<BigInt> = OnceCell::new();
+    /// The flavor of the protocol used by this deployment
+    static ref FLAVOR: Flavor = Flavor::from_env().unwrap();
 
-    fn get_p() -> &'static BigInt {
-        P.get().unwrap()
-    }
+    /// The identity of the Auth Server, which is bound into the login proofs
+    static ref SERVER_ID: String = transcript::server_id_from_env();
 
-    fn get_g() -> &'static BigInt {
-        G.get().unwrap()
-    }
+    /// The group of the modp flavor, over the parameter set or parameter file of this deployment
+    /// (checked by [`init`] at startup)
+    static ref MODP_GROUP: Result<ModPGroup, zkp_core::Error> = params::group_from_env();
 
-    fn get_h() -> &'static BigInt {
-        H.get().unwrap()
-    }
-
-    pub fn gen_random_with_n_bits<const N: u64>() -> BigInt {
-        let mut rng = rand::thread_rng();
-        rng.sample::<BigInt, _>(RandomBits::new(N)).abs()
-    }
-
-    /// Initialise the ZKP Prover
-    pub fn init() {
-        let _ = P.set(BigInt::from(2u32).pow(255) - BigInt::from(19u32));
-        let _ = G.set(BigInt::from(5u32));
-        let _ = H.set(BigInt::from(3u32));
-    }
+    /// The ZKP Chaum-Pedersen Provers, one per flavor - the nonces of their proofs are derived,
+    /// so that a weak RNG on the client does not leak the secrets
+    static ref MODP_PROVER: Prover<ModPGroup> = Prover::new(MODP_GROUP.clone().unwrap())
+        .with_nonce_generation(NonceGeneration::Derived);
+    static ref RISTRETTO255_PROVER: Prover<Ristretto255Group> =
+        Prover::new(Ristretto255Group::default()).with_nonce_generation(NonceGeneration::Derived);
+}
 
-    /// Generate the public data for the Chaum-Pedersen protocol - (y1, y2)
-    pub fn gen_public(x: &BigInt) -> (BigInt, BigInt) {
-        (get_g().modpow(&x, get_p()), get_h().modpow(&x, get_p()))
-    }
+/// The wrapper module for the Auth protocol buffer definition (proto/zkp_auth.protoh)
+pub mod zkp_auth {
+    tonic::include_proto!("zkp_auth");
+}
 
-    /// Generate the `k` for generating the challenge request parameters, (r1, r2)
-    pub fn gen_random(k: &BigInt) -> (BigInt, BigInt) {
-        debug!("k = {:?}", k);
-        (get_g().modpow(&k, get_p()), get_h().modpow(&k, get_p()))
+impl From<zkp_auth::KdfParams> for KdfParams {
+    fn from(params: zkp_auth::KdfParams) -> Self {
+        KdfParams {
+            memory_kib: params.memory_kib,
+            iterations: params.iterations,
+            parallelism: params.parallelism,
+        }
     }
+}
 
-    /// Generate `s`, the challenge answer (s = k - c * x)
-    pub fn challenge_answer(c: BigInt, k: BigInt, x: BigInt) -> BigInt {
-        debug!("c = {c:?}, k = {k:?}, x = {x:?}");
-        k.clone() - c * x
+impl From<KdfParams> for zkp_auth::KdfParams {
+    fn from(params: KdfParams) -> Self {
+        zkp_auth::KdfParams {
+            memory_kib: params.memory_kib,
+            iterations: params.iterations,
+            parallelism: params.parallelism,
+        }
     }
+}
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
-
-        #[test]
-        fn test_init() {
-            init();
+/// Resolve the configuration of the client from the environment (the flavor of the protocol,
+/// and the parameters of the modp flavor), so that an invalid one fails at startup rather than
+/// in the first request
+pub fn init() -> Result<(), zkp_core::Error> {
+    Flavor::from_env()?;
+    lazy_static::initialize(&FLAVOR);
 
-            assert_eq!(*get_p(), BigInt::from(2u32).pow(255) - BigInt::from(19u32));
-            assert_eq!(*get_g(), BigInt::from(5u32));
-            assert_eq!(*get_h(), BigInt::from(3u32));
+    if *FLAVOR == Flavor::ModP {
+        if let Err(err) = &*MODP_GROUP {
+            return Err(err.clone());
         }
+        lazy_static::initialize(&MODP_PROVER);
     }
-}
 
-/// The wrapper module for the Auth protocol buffer definition (proto/zkp_auth.protoh)
-pub mod zkp_auth {
-    tonic::include_proto!("zkp_auth");
+    Ok(())
 }
 
+/// The environment variable from which the URI of the Auth Server is read, if set
+pub const SERVER_ADDR_ENV_VAR: &str = "ZKP_SERVER_ADDR";
+
 /// Connect to the Auth Server
 pub async fn connect_to_zkp_server() -> Result<AuthClient<Channel>, Box<dyn std::error::Error>> {
     info!("Connecting to the Auth Server");
 
     // In production, this would be read off of configuration files (encrypted)
-    const DOCKER_ADDR: &'static str = "zkp_server";
-    const NON_DOCKER_ADDR: &'static str = "0.0.0.0";
+    const DOCKER_ADDR: &str = "zkp_server";
+    const NON_DOCKER_ADDR: &str = "0.0.0.0";
 
-    let zkp_server_addr = std::env::var("DOCKER_MODE").map_or(NON_DOCKER_ADDR, |_| DOCKER_ADDR);
-    let auth_client = AuthClient::connect(format!("http://{}:9999", zkp_server_addr)).await?;
+    // the full URI of the server may also be given, e.g., `http://127.0.0.1:9999`
+    let zkp_server_uri = std::env::var(SERVER_ADDR_ENV_VAR).unwrap_or_else(|_| {
+        let zkp_server_addr = std::env::var("DOCKER_MODE").map_or(NON_DOCKER_ADDR, |_| DOCKER_ADDR);
+        format!("http://{}:9999", zkp_server_addr)
+    });
+    let auth_client = AuthClient::connect(zkp_server_uri).await?;
 
     Ok(auth_client)
 }
@@ -114,7 +139,7 @@ pub async fn connect_to_zkp_server() -> Result<AuthClient<Channel>, Box<dyn std:
 /// Register the user with the Auth Server
 pub async fn register(
     user: String,
-    password: String,
+    password: Secret<String>,
 ) -> Result<ZkpClientRegistrationStatus, Box<dyn std::error::Error>> {
     info!("Preparing to register with the Auth Server");
 
@@ -124,23 +149,21 @@ pub async fn register(
 
     let mut auth_client = connect_to_zkp_server().await?;
 
-    // Initialise the ZKP Prover
-    zkp_prover::init();
-
-    let secret = BigInt::parse_bytes(password.as_bytes(), 10).unwrap();
-    let (y1, y2) = zkp_prover::gen_public(&secret.clone());
-
-    debug!("y1 = {y1:?}, y2 = {y2:?}");
+    let registration = Registration {
+        salt: kdf::gen_salt(),
+        kdf: KdfParams::default(),
+    };
 
-    let request = tonic::Request::new(RegisterRequest {
-        user: user.clone(),
-        y1: y1.to_string(),
-        y2: y2.to_string(),
-    });
+    let request = match *FLAVOR {
+        Flavor::ModP => register_request(&MODP_PROVER, &user, &password, &registration)?,
+        Flavor::Ristretto255 => {
+            register_request(&RISTRETTO255_PROVER, &user, &password, &registration)?
+        }
+    };
 
-    auth_client.register(request).await?;
+    auth_client.register(tonic::Request::new(request)).await?;
 
-    // add user to the set of registered users
+    // add user to the registered users
     REGISTERED_USERS.lock().unwrap().insert(user);
 
     info!("User registered");
@@ -148,51 +171,141 @@ pub async fn register(
     Ok(ZkpClientRegistrationStatus::Registered)
 }
 
-/// Attempt to authenticate the user with the Auth Server
+/// Generate the public data, (y1, y2), for the user, along with a proof of knowledge of the secret
+fn register_request<G: Group>(
+    prover: &Prover<G>,
+    user: &str,
+    password: &Secret<String>,
+    registration: &Registration,
+) -> Result<RegisterRequest, zkp_core::Error> {
+    let secret = password_to_secret(
+        prover.group(),
+        password,
+        &registration.salt,
+        &registration.kdf,
+    )?;
+    let (y1, y2) = prover.gen_public(secret.expose());
+    let proof = prover.prove(
+        secret.expose(),
+        Transcript::registration(&SERVER_ID, user, &registration.salt),
+    );
+
+    debug!("y1 = {y1:?}, y2 = {y2:?}");
+
+    Ok(RegisterRequest {
+        user: user.to_string(),
+        y1: prover.group().encode_element(&y1),
+        y2: prover.group().encode_element(&y2),
+        salt: hex::encode(&registration.salt),
+        kdf: Some(registration.kdf.into()),
+        r1: prover.group().encode_element(&proof.r1),
+        r2: prover.group().encode_element(&proof.r2),
+        s: prover.group().encode_scalar(&proof.s),
+    })
+}
+
+/// Attempt to authenticate the user with the Auth Server, with a non-interactive proof (a single
+/// round trip, once the salt and the KDF parameters of the user have been fetched)
 pub async fn login(
     user: String,
-    password: String,
+    password: Secret<String>,
 ) -> Result<ZkpClientAuthenticationStatus, Box<dyn std::error::Error>> {
     info!("Preparing to log on to the Auth Server");
 
-    if !REGISTERED_USERS.lock().unwrap().contains(&user) {
-        return Ok(ZkpClientAuthenticationStatus::UnregisteredUser);
-    }
-
     let mut auth_client = connect_to_zkp_server().await?;
 
-    // Commitment
-    let password = BigInt::parse_bytes(password.as_bytes(), 10).unwrap();
+    let Some(registration) = login_parameters(&mut auth_client, &user).await? else {
+        return Ok(ZkpClientAuthenticationStatus::UnregisteredUser);
+    };
 
-    let k = zkp_prover::gen_random_with_n_bits::<128>();
-    let (r1, r2) = zkp_prover::gen_random(&k);
+    let request = match *FLAVOR {
+        Flavor::ModP => login_with_proof_request(&MODP_PROVER, &user, &password, &registration)?,
+        Flavor::Ristretto255 => {
+            login_with_proof_request(&RISTRETTO255_PROVER, &user, &password, &registration)?
+        }
+    };
 
-    // Challenge request
-    let challenge_response = auth_client
-        .create_authentication_challenge(tonic::Request::new(AuthenticationChallengeRequest {
-            user: user.clone(),
-            r1: r1.to_string(),
-            r2: r2.to_string(),
-        }))
-        .await?;
+    authentication_status(
+        user,
+        auth_client
+            .login_with_proof(tonic::Request::new(request))
+            .await,
+    )
+}
 
-    debug!("challenge response: {challenge_response:?}");
+/// Attempt to authenticate the user with the Auth Server, with the interactive protocol (a
+/// challenge request, followed by the challenge answer)
+pub async fn login_interactive(
+    user: String,
+    password: Secret<String>,
+) -> Result<ZkpClientAuthenticationStatus, Box<dyn std::error::Error>> {
+    info!("Preparing to log on to the Auth Server");
 
-    let challenge_response = challenge_response.into_inner();
-    let (auth_id, c) = (
-        challenge_response.auth_id,
-        BigInt::parse_bytes(challenge_response.c.as_bytes(), 10).unwrap(),
-    );
+    let mut auth_client = connect_to_zkp_server().await?;
 
-    // Challenge answer
-    // Authentication status
-    match auth_client
-        .verify_authentication(tonic::Request::new(AuthenticationAnswerRequest {
-            auth_id,
-            s: zkp_prover::challenge_answer(c, k, password).to_string(),
+    match *FLAVOR {
+        Flavor::ModP => authenticate(&MODP_PROVER, &mut auth_client, user, &password).await,
+        Flavor::Ristretto255 => {
+            authenticate(&RISTRETTO255_PROVER, &mut auth_client, user, &password).await
+        }
+    }
+}
+
+/// Fetch the salt and the KDF parameters stored by the Auth Server at the registration of the
+/// user (`None` if the user is not registered)
+async fn login_parameters(
+    auth_client: &mut AuthClient<Channel>,
+    user: &str,
+) -> Result<Option<Registration>, Box<dyn std::error::Error>> {
+    let response = match auth_client
+        .get_login_parameters(tonic::Request::new(LoginParametersRequest {
+            user: user.to_string(),
         }))
         .await
     {
+        Ok(response) => response.into_inner(),
+        Err(status) if status.code() == tonic::Code::NotFound => return Ok(None),
+        Err(status) => return Err(status.into()),
+    };
+
+    Registration::decode(&response.salt, response.kdf).map(Some)
+}
+
+/// Generate the non-interactive proof for the login of the user, bound to the Auth Server and
+/// the current time
+fn login_with_proof_request<G: Group>(
+    prover: &Prover<G>,
+    user: &str,
+    password: &Secret<String>,
+    registration: &Registration,
+) -> Result<LoginWithProofRequest, zkp_core::Error> {
+    let group = prover.group();
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let secret = password_to_secret(group, password, &registration.salt, &registration.kdf)?;
+    let proof = prover.prove(
+        secret.expose(),
+        Transcript::login(&SERVER_ID, user, timestamp),
+    );
+
+    Ok(LoginWithProofRequest {
+        user: user.to_string(),
+        r1: group.encode_element(&proof.r1),
+        r2: group.encode_element(&proof.r2),
+        s: group.encode_scalar(&proof.s),
+        timestamp,
+    })
+}
+
+/// The authentication status, from the response of the Auth Server
+fn authentication_status(
+    user: String,
+    response: Result<tonic::Response<AuthenticationAnswerResponse>, tonic::Status>,
+) -> Result<ZkpClientAuthenticationStatus, Box<dyn std::error::Error>> {
+    match response {
         Ok(response) => {
             info!("User {user:?} authenticated");
 
@@ -211,5 +324,227 @@ pub async fn login(
     }
 }
 
+/// Run the Chaum-Pedersen protocol against the Auth Server, with the password as the secret
+async fn authenticate<G: Group>(
+    prover: &Prover<G>,
+    auth_client: &mut AuthClient<Channel>,
+    user: String,
+    password: &Secret<String>,
+) -> Result<ZkpClientAuthenticationStatus, Box<dyn std::error::Error>> {
+    let group = prover.group();
+
+    // Commitment
+    let k = prover.gen_nonce();
+    let (r1, r2) = prover.gen_random(&k);
+
+    // Challenge request
+    let challenge_response = match auth_client
+        .create_authentication_challenge(tonic::Request::new(AuthenticationChallengeRequest {
+            user: user.clone(),
+            r1: group.encode_element(&r1),
+            r2: group.encode_element(&r2),
+        }))
+        .await
+    {
+        Ok(response) => response,
+        Err(status) if status.code() == tonic::Code::NotFound => {
+            return Ok(ZkpClientAuthenticationStatus::UnregisteredUser)
+        }
+        Err(status) => return Err(status.into()),
+    };
+
+    debug!("challenge response: {challenge_response:?}");
+
+    let challenge_response = challenge_response.into_inner();
+    let (auth_id, c) = (
+        challenge_response.auth_id,
+        group.decode_scalar(&challenge_response.c)?,
+    );
+
+    // the secret is derived with the salt and the KDF parameters stored at registration
+    let registration = Registration::decode(&challenge_response.salt, challenge_response.kdf)?;
+    let secret = password_to_secret(group, password, &registration.salt, &registration.kdf)?;
+
+    // Challenge answer
+    let response = auth_client
+        .verify_authentication(tonic::Request::new(AuthenticationAnswerRequest {
+            auth_id,
+            s: group.encode_scalar(&prover.challenge_answer(&c, &k, secret.expose())?),
+        }))
+        .await;
+
+    // Authentication status
+    authentication_status(user, response)
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+    use warp::Reply;
+    use zkp_core::redact;
+    use zkp_server::zkp_auth::auth_server::AuthServer;
+
+    use super::*;
+    use crate::rest::handlers;
+    use crate::rest::models::LoginDetails;
+
+    /// A writer which appends to a shared buffer
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Capture {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// The value of the (string) field `name` of the first message `message` in the logs
+    fn logged_field<'a>(output: &'a str, message: &str, name: &str) -> &'a str {
+        let start = output.find(message).unwrap();
+        let start = start + output[start..].find(&format!("{name}: \"")).unwrap() + name.len() + 3;
+        let end = start + output[start..].find('"').unwrap();
+
+        &output[start..end]
+    }
+
+    /// Serve the Auth requests in process, on a free port, and point the client at it
+    async fn start_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        std::env::set_var(
+            SERVER_ADDR_ENV_VAR,
+            format!("http://{}", listener.local_addr().unwrap()),
+        );
+
+        tokio::spawn(
+            Server::builder()
+                .add_service(AuthServer::new(zkp_server::Verifier::<ModPGroup>::default()))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+    }
+
+    #[test]
+    fn test_kdf_parameters_from_the_server() {
+        let salt = hex::encode(kdf::gen_salt());
+        let decode = |kdf: KdfParams| Registration::decode(&salt, Some(kdf.into()));
+
+        assert!(decode(KdfParams::default()).is_ok());
+        assert!(decode(KdfParams::MAX).is_ok());
+
+        // too cheap to protect the password, or too costly for the client
+        for kdf in [
+            KdfParams {
+                memory_kib: 8,
+                iterations: 1,
+                parallelism: 1,
+            },
+            KdfParams {
+                memory_kib: 4 * 1024 * 1024,
+                ..KdfParams::MAX
+            },
+        ] {
+            let err = decode(kdf).unwrap_err();
+            assert!(err.downcast_ref::<zkp_core::Error>().is_some(), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_secrets_are_redacted_from_logs() {
+        const PASSWORD: &str = "correct horse battery staple";
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let writer = output.clone();
+
+        // the client and the server run on this thread, so all of their logs are captured
+        let _guard = tracing::subscriber::set_default(
+            tracing_subscriber::fmt()
+                .with_max_level(tracing::Level::TRACE)
+                .with_ansi(false)
+                .fmt_fields(redact::fields())
+                .with_writer(move || Capture(writer.clone()))
+                .finish(),
+        );
+
+        start_server().await;
+
+        let details = || LoginDetails {
+            user: "Bob".into(),
+            password: Secret::new(PASSWORD.into()),
+        };
+
+        let response = handlers::handle_registration(details())
+            .await
+            .unwrap()
+            .into_response();
+        assert!(response.status().is_success());
+
+        let response = handlers::handle_login(details())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains(r#""status":"authenticated""#));
+
+        assert!(matches!(
+            login_interactive("Bob".into(), Secret::new(PASSWORD.into()))
+                .await
+                .unwrap(),
+            ZkpClientAuthenticationStatus::Authenticated { .. }
+        ));
+
+        let registration = login_parameters(&mut connect_to_zkp_server().await.unwrap(), "Bob")
+            .await
+            .unwrap()
+            .unwrap();
+        let group = MODP_PROVER.group();
+        let x = password_to_secret(
+            group,
+            &Secret::new(PASSWORD.into()),
+            &registration.salt,
+            &registration.kdf,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+
+        assert!(output.contains("password: [REDACTED]"), "{output}");
+        assert!(!output.contains(PASSWORD), "{output}");
+        assert!(
+            !output.contains(&group.encode_scalar(x.expose())),
+            "{output}"
+        );
+
+        // the nonce of the interactive login, k = s + c * x, from the (public) challenge and
+        // answer in the logs
+        let c = group
+            .decode_scalar(logged_field(
+                &output,
+                "AuthenticationChallengeResponse",
+                "c",
+            ))
+            .unwrap();
+        let s = group
+            .decode_scalar(logged_field(&output, "AuthenticationAnswerRequest", "s"))
+            .unwrap();
+        let k = group.scalar_add(&s, &group.scalar_mul(&c, x.expose()));
+        assert!(!output.contains(&group.encode_scalar(&k)), "{output}");
+
+        for name in redact::SECRET_FIELDS {
+            for (at, _) in output.match_indices(&format!(" {name}=")) {
+                assert!(
+                    output[at + name.len() + 2..].starts_with(redact::REDACTED),
+                    "{output}"
+                );
+            }
+        }
+    }
+}
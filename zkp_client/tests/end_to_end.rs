@@ -0,0 +1,181 @@
+//! End-to-end tests of the whole application - the Auth Server and the REST interface of the
+//! client are both served in process, on ephemeral ports, and the users register and log in
+//! over HTTP, as they would with `curl`.
+
+use std::sync::{mpsc, OnceLock};
+
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use warp::http::{Method, Request, StatusCode};
+use warp::hyper::{body, Body, Client};
+
+use zkp_client::rest::filters;
+use zkp_client::zkp_auth_client::{connect_to_zkp_server, zkp_auth, SERVER_ADDR_ENV_VAR};
+use zkp_core::kdf::{self, password_to_secret};
+use zkp_core::transcript::DEFAULT_SERVER_ID;
+use zkp_core::{Group, KdfParams, ModPGroup, Prover, Secret, Transcript};
+use zkp_server::{zkp_auth::auth_server::AuthServer, Verifier};
+
+/// Serve the Auth requests, and the REST interface of a client pointed at them, on free ports,
+/// and send the URL of the REST interface once both are listening
+async fn serve(url: mpsc::Sender<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    std::env::set_var(
+        SERVER_ADDR_ENV_VAR,
+        format!("http://{}", listener.local_addr().unwrap()),
+    );
+
+    tokio::spawn(
+        Server::builder()
+            .add_service(AuthServer::new(Verifier::<ModPGroup>::default()))
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    let (address, client) = warp::serve(filters::ext_clients()).bind_ephemeral(([127, 0, 0, 1], 0));
+    url.send(format!("http://{address}")).unwrap();
+
+    client.await;
+}
+
+/// The URL of the REST interface of the client, started (along with the Auth Server) on the
+/// first call - both run on a runtime of their own, so that they outlive the runtime of any
+/// one test
+fn client_url() -> &'static str {
+    static URL: OnceLock<String> = OnceLock::new();
+
+    URL.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(|| {
+            tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(serve(sender))
+        });
+
+        receiver.recv().unwrap()
+    })
+}
+
+/// POST the user and the password to the endpoint of the client, and return the status and the
+/// (JSON) body of the response
+async fn post(endpoint: &str, user: &str, password: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(format!("{}/{endpoint}", client_url()))
+        .header("Content-Type", "application/json")
+        .body(Body::from(
+            json!({ "user": user, "password": password }).to_string(),
+        ))
+        .unwrap();
+
+    let response = Client::new().request(request).await.unwrap();
+    let status = response.status();
+    let body = body::to_bytes(response.into_body()).await.unwrap();
+
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_register() {
+    let (status, body) = post("register", "Amanda", "12345").await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body, json!({ "user": "Amanda", "status": "registered" }));
+
+    let (status, body) = post("register", "Amanda", "54321").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body,
+        json!({ "user": "Amanda", "status": "already registered" })
+    );
+}
+
+#[tokio::test]
+async fn test_login() {
+    let (status, _) = post("register", "Bob", "000191").await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let mut session_ids = Vec::new();
+    for _ in 0..2 {
+        let (status, body) = post("login", "Bob", "000191").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["user"], "Bob");
+        assert_eq!(body["status"], "authenticated");
+
+        session_ids.push(body["session_id"].as_str().unwrap().to_string());
+    }
+    assert_ne!(session_ids[0], session_ids[1]);
+}
+
+#[tokio::test]
+async fn test_login_with_wrong_password() {
+    let (status, _) = post("register", "Carol", "correct horse battery staple").await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, body) = post("login", "Carol", "correct horse battery stapler").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        body,
+        json!({
+            "user": "Carol",
+            "status": "not authenticated - user authentication failed",
+            "session_id": null,
+        })
+    );
+
+    // the failed attempt does not lock the user out
+    let (status, body) = post("login", "Carol", "correct horse battery staple").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "authenticated");
+}
+
+#[tokio::test]
+async fn test_login_of_unregistered_user() {
+    let (status, body) = post("login", "Dave", "12345").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        body,
+        json!({ "user": "Dave", "status": "unregistered user", "session_id": null })
+    );
+}
+
+#[tokio::test]
+async fn test_login_of_user_registered_elsewhere() {
+    const PASSWORD: &str = "hunter2";
+
+    client_url();
+
+    // register Erin straight with the Auth Server, as another client (or this one, before a
+    // restart) would
+    let prover = Prover::new(ModPGroup::default());
+    let group = prover.group();
+    let salt = kdf::gen_salt();
+    let kdf = KdfParams::default();
+
+    let x = password_to_secret(group, &Secret::new(PASSWORD.into()), &salt, &kdf).unwrap();
+    let (y1, y2) = prover.gen_public(x.expose());
+    let proof = prover.prove(
+        x.expose(),
+        Transcript::registration(DEFAULT_SERVER_ID, "Erin", &salt),
+    );
+
+    connect_to_zkp_server()
+        .await
+        .unwrap()
+        .register(zkp_auth::RegisterRequest {
+            user: "Erin".into(),
+            y1: group.encode_element(&y1),
+            y2: group.encode_element(&y2),
+            salt: hex::encode(&salt),
+            kdf: Some(kdf.into()),
+            r1: group.encode_element(&proof.r1),
+            r2: group.encode_element(&proof.r2),
+            s: group.encode_scalar(&proof.s),
+        })
+        .await
+        .unwrap();
+
+    let (status, body) = post("login", "Erin", PASSWORD).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "authenticated");
+}
@@ -0,0 +1,32 @@
+//! The startup of the client - an invalid configuration in the environment stops the client with
+//! an error before it serves any request, instead of panicking in the first one.
+
+use std::process::Command;
+
+/// Run the client with the environment variable set, and return its stderr - the client must
+/// exit with an error
+fn start_with(name: &str, value: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_zkp_client"))
+        .env(name, value)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+    assert!(!output.status.success(), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+
+    stderr
+}
+
+#[test]
+fn test_invalid_flavor() {
+    assert!(start_with("ZKP_FLAVOR", "rsa").contains("UnknownFlavor"));
+}
+
+#[test]
+fn test_invalid_parameters() {
+    assert!(start_with("ZKP_PARAMS", "modp1024").contains("UnknownParameterSet"));
+    assert!(
+        start_with("ZKP_PARAMS_FILE", "/nonexistent/params.json").contains("InvalidParameterFile")
+    );
+}
@@ -0,0 +1,55 @@
+//! The REST interface of the client when the Auth Server cannot be reached - the requests are
+//! answered with a 503, instead of a panic of the handlers. (This is a test binary of its own,
+//! since the address of the Auth Server is read from the environment of the process.)
+
+use std::net::TcpListener;
+
+use serde_json::{json, Value};
+use warp::http::{Method, Request, StatusCode};
+use warp::hyper::{body, Body, Client};
+
+use zkp_client::rest::filters;
+use zkp_client::zkp_auth_client::SERVER_ADDR_ENV_VAR;
+
+#[tokio::test]
+async fn test_unreachable_server() {
+    // a free port, with nothing listening on it
+    let address = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    std::env::set_var(SERVER_ADDR_ENV_VAR, format!("http://{address}"));
+
+    let (address, client) = warp::serve(filters::ext_clients()).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(client);
+
+    for (endpoint, expected) in [
+        (
+            "register",
+            json!({ "user": "Bob", "status": "not registered - the Auth Server is unreachable" }),
+        ),
+        (
+            "login",
+            json!({
+                "user": "Bob",
+                "status": "not authenticated - the Auth Server is unreachable",
+                "session_id": null,
+            }),
+        ),
+    ] {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("http://{address}/{endpoint}"))
+            .header("Content-Type", "application/json")
+            .body(Body::from(
+                json!({ "user": "Bob", "password": "000191" }).to_string(),
+            ))
+            .unwrap();
+
+        let response = Client::new().request(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let body = body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), expected);
+    }
+}
@@ -0,0 +1,64 @@
+//! Benchmarks of the arithmetic backends of the `modp` flavor, on the exponentiations done by the
+//! Prover and the Verifier.
+//!
+//!   $ cargo bench -p zkp_core --bench backends
+//!   $ cargo bench -p zkp_core --bench backends --features gmp
+
+use criterion::measurement::WallTime;
+use criterion::{
+    black_box, criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Criterion,
+};
+
+use zkp_core::backend::{Field25519, FixedWidth2048, FixedWidth256, Montgomery, NumBigint};
+use zkp_core::{Backend, Group, ParameterSet, Prover, Verifier};
+
+fn bench_backend<B: Backend>(benches: &mut BenchmarkGroup<'_, WallTime>, set: ParameterSet) {
+    let group = set.group().to_backend::<B>();
+    let prover = Prover::new(group.clone());
+    let verifier = Verifier::new(group.clone());
+
+    let x = group.random_scalar();
+    let (y1, y2) = prover.gen_public(&x);
+    let k = prover.gen_nonce();
+    let (r1, r2) = prover.gen_random(&k);
+    let c = verifier.request_challenge();
+    let s = prover.challenge_answer(&c, &k, &x).unwrap();
+
+    let id = |operation: &str| BenchmarkId::new(format!("{operation}/{}", B::NAME), set);
+
+    benches.bench_function(id("g^s"), |b| {
+        b.iter(|| black_box(group.exp(group.g(), &s)))
+    });
+
+    benches.bench_function(id("y1^c"), |b| b.iter(|| black_box(group.exp(&y1, &c))));
+
+    benches.bench_function(id("g^s.y1^c"), |b| {
+        b.iter(|| black_box(group.multi_exp(&[(group.g(), &s), (&y1, &c)])))
+    });
+
+    benches.bench_function(id("verify"), |b| {
+        b.iter(|| black_box(verifier.verify(&s, &c, &y1, &y2, &r1, &r2)))
+    });
+}
+
+fn bench_backends(criterion: &mut Criterion) {
+    let mut benches = criterion.benchmark_group("backends");
+
+    bench_backend::<NumBigint>(&mut benches, ParameterSet::P25519);
+    bench_backend::<Montgomery>(&mut benches, ParameterSet::P25519);
+    bench_backend::<Field25519>(&mut benches, ParameterSet::P25519);
+    bench_backend::<FixedWidth256>(&mut benches, ParameterSet::P25519);
+    #[cfg(feature = "gmp")]
+    bench_backend::<zkp_core::backend::Gmp>(&mut benches, ParameterSet::P25519);
+
+    bench_backend::<NumBigint>(&mut benches, ParameterSet::Ffdhe2048);
+    bench_backend::<Montgomery>(&mut benches, ParameterSet::Ffdhe2048);
+    bench_backend::<FixedWidth2048>(&mut benches, ParameterSet::Ffdhe2048);
+    #[cfg(feature = "gmp")]
+    bench_backend::<zkp_core::backend::Gmp>(&mut benches, ParameterSet::Ffdhe2048);
+
+    benches.finish();
+}
+
+criterion_group!(benches, bench_backends);
+criterion_main!(benches);
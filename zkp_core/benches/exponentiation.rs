@@ -0,0 +1,80 @@
+//! Benchmarks of the exponentiations done by the Verifier, comparing the previous code path (a
+//! separate `modpow` for every power) against the fixed-base tables and the Straus/Shamir trick,
+//! and the constant-time exponentiation of the Prover.
+//!
+//!   $ cargo bench -p zkp_core
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_bigint::BigUint;
+
+use zkp_core::{Group, ModPGroup, ParameterSet, Prover, Verifier};
+
+/// g^s . y1^c and h^s . y2^c, computed with a separate `modpow` for every power
+fn verify_with_modpow(
+    group: &ModPGroup,
+    s: &BigUint,
+    c: &BigUint,
+    (y1, y2): (&BigUint, &BigUint),
+) -> (BigUint, BigUint) {
+    let p = group.p();
+
+    (
+        (group.g().modpow(s, p) * y1.modpow(c, p)) % p,
+        (group.h().modpow(s, p) * y2.modpow(c, p)) % p,
+    )
+}
+
+fn bench_exponentiation(criterion: &mut Criterion) {
+    let mut benches = criterion.benchmark_group("exponentiation");
+
+    for set in [ParameterSet::P25519, ParameterSet::Ffdhe2048] {
+        let group = set.group();
+        let prover = Prover::new(group.clone());
+        let verifier = Verifier::new(group.clone());
+
+        let x = group.random_scalar();
+        let (y1, y2) = prover.gen_public(&x);
+        let k = prover.gen_nonce();
+        let (r1, r2) = prover.gen_random(&k);
+        let c = verifier.request_challenge();
+        let s = prover.challenge_answer(&c, &k, &x).unwrap();
+
+        benches.bench_with_input(BenchmarkId::new("g^s/modpow", set), &s, |b, s| {
+            b.iter(|| black_box(group.g().modpow(s, group.p())))
+        });
+
+        benches.bench_with_input(BenchmarkId::new("g^s/fixed-base", set), &s, |b, s| {
+            b.iter(|| black_box(group.exp(group.g(), s)))
+        });
+
+        benches.bench_with_input(BenchmarkId::new("g^x/constant-time", set), &x, |b, x| {
+            b.iter(|| black_box(group.exp_secret(group.g(), x)))
+        });
+
+        benches.bench_with_input(BenchmarkId::new("g^s.y1^c/modpow", set), &s, |b, s| {
+            b.iter(|| {
+                black_box((group.g().modpow(s, group.p()) * y1.modpow(&c, group.p())) % group.p())
+            })
+        });
+
+        benches.bench_with_input(BenchmarkId::new("g^s.y1^c/multi-exp", set), &s, |b, s| {
+            b.iter(|| black_box(group.multi_exp(&[(group.g(), s), (&y1, &c)])))
+        });
+
+        benches.bench_with_input(BenchmarkId::new("verify/modpow", set), &s, |b, s| {
+            b.iter(|| {
+                let (r1_prime, r2_prime) = verify_with_modpow(&group, s, &c, (&y1, &y2));
+                black_box(r1_prime == r1 && r2_prime == r2)
+            })
+        });
+
+        benches.bench_with_input(BenchmarkId::new("verify/multi-exp", set), &s, |b, s| {
+            b.iter(|| black_box(verifier.verify(s, &c, &y1, &y2, &r1, &r2)))
+        });
+    }
+
+    benches.finish();
+}
+
+criterion_group!(benches, bench_exponentiation);
+criterion_main!(benches);
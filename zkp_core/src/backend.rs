@@ -0,0 +1,351 @@
+//! The arithmetic modulo p behind [`ModPGroup`](crate::ModPGroup) - its exponentiations, and the
+//! fixed-base tables and multi-exponentiations of [`multiexp`](crate::multiexp).
+//!
+//! A [`Backend`] is a context for arithmetic modulo a fixed odd modulus, with numbers held in its
+//! own representation (a [`Backend::Residue`]) between the conversions from and to `BigUint`:
+//!
+//!   * [`NumBigint`] - plain `num-bigint`, with a division for every reduction.
+//!   * [`Montgomery`] - Montgomery form on 64-bit limbs as wide as the modulus.
+//!   * [`Field25519`] - five 51-bit limbs, for the Curve25519 prime p = 2^255 - 19 only.
+//!   * [`Auto`] - [`Field25519`] for the Curve25519 prime, and [`Montgomery`] for any other
+//!     modulus (the default).
+//!   * [`FixedWidth`] - Montgomery form on the fixed-width integers of `crypto-bigint`, for a
+//!     modulus of at most `LIMBS` limbs.
+//!   * `Gmp` - GMP, through `rug` (with the `gmp` feature).
+//!
+//! The exponentiations with a secret exponent are computed in constant time - with the backend
+//! if it supports it (see [`Backend::pow_secret`]; [`Field25519`] does, and so [`Auto`] does for
+//! the Curve25519 prime), or else with [`ConstantTimeModulus`](crate::ct::ConstantTimeModulus).
+//! If neither applies (the modulus is even, or too wide for `ConstantTimeModulus`), they fall
+//! back to the variable-time `modpow` of `BigUint`.
+//!
+//! They can be compared with:
+//!
+//!   $ cargo bench -p zkp_core --features gmp --bench backends
+
+use std::fmt::Debug;
+
+use num_bigint::BigUint;
+use num_traits::One;
+
+use crate::field25519::FieldElement;
+use crate::multiexp;
+
+pub use crate::ct::FixedWidth;
+pub use crate::field25519::Field25519;
+#[cfg(feature = "gmp")]
+pub use crate::gmp::Gmp;
+pub use crate::multiexp::Montgomery;
+
+/// [`FixedWidth`], for a modulus of up to 256 bits (e.g., for
+/// [`ParameterSet::P25519`](crate::ParameterSet::P25519))
+pub type FixedWidth256 = FixedWidth<{ crypto_bigint::U256::LIMBS }>;
+
+/// [`FixedWidth`], for a modulus of up to 2048 bits (e.g., for
+/// [`ParameterSet::Ffdhe2048`](crate::ParameterSet::Ffdhe2048))
+pub type FixedWidth2048 = FixedWidth<{ crypto_bigint::U2048::LIMBS }>;
+
+/// [`FixedWidth`], for a modulus of up to 3072 bits (e.g., for
+/// [`ParameterSet::Ffdhe3072`](crate::ParameterSet::Ffdhe3072))
+pub type FixedWidth3072 = FixedWidth<{ crypto_bigint::U3072::LIMBS }>;
+
+/// Arithmetic modulo a fixed odd modulus
+pub trait Backend: Clone + Debug + PartialEq + Eq + Send + Sync + 'static {
+    /// A number modulo the modulus, in the representation of the backend
+    type Residue: Clone + Debug + PartialEq + Eq + Send + Sync;
+
+    /// A short name for the backend (in the benchmarks)
+    const NAME: &'static str;
+
+    /// The context for the modulus, or `None` if it is even (or 1), or too wide for the backend
+    fn new(modulus: &BigUint) -> Option<Self>;
+
+    /// Convert x (any non-negative integer) into a residue
+    fn to_residue(&self, x: &BigUint) -> Self::Residue;
+
+    /// Convert a residue back into an integer, in [0, modulus)
+    fn to_integer(&self, a: &Self::Residue) -> BigUint;
+
+    /// 1, as a residue
+    fn one(&self) -> Self::Residue;
+
+    /// a . b mod the modulus
+    fn mul(&self, a: &Self::Residue, b: &Self::Residue) -> Self::Residue;
+
+    /// a . a mod the modulus
+    fn square(&self, a: &Self::Residue) -> Self::Residue {
+        self.mul(a, a)
+    }
+
+    /// base ^ exponent mod the modulus
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint;
+
+    /// base ^ exponent mod the modulus, in constant time, for an exponent smaller than
+    /// 2^exponent_bits - or `None` if the backend does not support it, in which case
+    /// [`ConstantTimeModulus`](crate::ct::ConstantTimeModulus) is used
+    fn pow_secret(
+        &self,
+        _base: &BigUint,
+        _exponent: &BigUint,
+        _exponent_bits: u64,
+    ) -> Option<BigUint> {
+        None
+    }
+
+    /// The product of base_i ^ exponent_i mod the modulus over all the terms (see
+    /// [`multiexp::multi_exp`])
+    fn multi_exp(&self, terms: &[(&BigUint, &BigUint)]) -> BigUint {
+        multiexp::multi_exp(self, terms)
+    }
+}
+
+/// Plain `num-bigint` arithmetic - every product is reduced with a division
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumBigint {
+    modulus: BigUint,
+}
+
+impl Backend for NumBigint {
+    type Residue = BigUint;
+
+    const NAME: &'static str = "num-bigint";
+
+    fn new(modulus: &BigUint) -> Option<Self> {
+        (modulus.bit(0) && !modulus.is_one()).then(|| NumBigint {
+            modulus: modulus.clone(),
+        })
+    }
+
+    fn to_residue(&self, x: &BigUint) -> BigUint {
+        x % &self.modulus
+    }
+
+    fn to_integer(&self, a: &BigUint) -> BigUint {
+        a.clone()
+    }
+
+    fn one(&self) -> BigUint {
+        BigUint::one()
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.modulus
+    }
+
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        base.modpow(exponent, &self.modulus)
+    }
+}
+
+/// [`Field25519`] for the Curve25519 prime, and [`Montgomery`] for any other modulus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Auto {
+    Field25519(Field25519),
+    Montgomery(Montgomery),
+}
+
+/// A residue of [`Auto`], in the representation of the backend it picked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoResidue {
+    Field25519(FieldElement),
+    Montgomery(Vec<u64>),
+}
+
+/// Run the expression with `$backend` bound to the backend that [`Auto`] picked
+macro_rules! dispatch {
+    ($self:expr, $backend:ident => $expression:expr) => {
+        match $self {
+            Auto::Field25519($backend) => $expression,
+            Auto::Montgomery($backend) => $expression,
+        }
+    };
+}
+
+const MISMATCH: &str = "the residue comes from another backend";
+
+impl Backend for Auto {
+    type Residue = AutoResidue;
+
+    const NAME: &'static str = "auto";
+
+    fn new(modulus: &BigUint) -> Option<Self> {
+        match Field25519::new(modulus) {
+            Some(field) => Some(Auto::Field25519(field)),
+            None => Montgomery::new(modulus).map(Auto::Montgomery),
+        }
+    }
+
+    fn to_residue(&self, x: &BigUint) -> AutoResidue {
+        match self {
+            Auto::Field25519(field) => AutoResidue::Field25519(field.to_residue(x)),
+            Auto::Montgomery(montgomery) => AutoResidue::Montgomery(montgomery.to_residue(x)),
+        }
+    }
+
+    fn to_integer(&self, a: &AutoResidue) -> BigUint {
+        match (self, a) {
+            (Auto::Field25519(field), AutoResidue::Field25519(a)) => field.to_integer(a),
+            (Auto::Montgomery(montgomery), AutoResidue::Montgomery(a)) => montgomery.to_integer(a),
+            _ => unreachable!("{MISMATCH}"),
+        }
+    }
+
+    fn one(&self) -> AutoResidue {
+        match self {
+            Auto::Field25519(field) => AutoResidue::Field25519(field.one()),
+            Auto::Montgomery(montgomery) => AutoResidue::Montgomery(Backend::one(montgomery)),
+        }
+    }
+
+    fn mul(&self, a: &AutoResidue, b: &AutoResidue) -> AutoResidue {
+        match (self, a, b) {
+            (Auto::Field25519(field), AutoResidue::Field25519(a), AutoResidue::Field25519(b)) => {
+                AutoResidue::Field25519(field.mul(a, b))
+            }
+            (
+                Auto::Montgomery(montgomery),
+                AutoResidue::Montgomery(a),
+                AutoResidue::Montgomery(b),
+            ) => AutoResidue::Montgomery(montgomery.mul(a, b)),
+            _ => unreachable!("{MISMATCH}"),
+        }
+    }
+
+    fn square(&self, a: &AutoResidue) -> AutoResidue {
+        match (self, a) {
+            (Auto::Field25519(field), AutoResidue::Field25519(a)) => {
+                AutoResidue::Field25519(field.square(a))
+            }
+            (Auto::Montgomery(montgomery), AutoResidue::Montgomery(a)) => {
+                AutoResidue::Montgomery(montgomery.square(a))
+            }
+            _ => unreachable!("{MISMATCH}"),
+        }
+    }
+
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        dispatch!(self, backend => backend.pow(base, exponent))
+    }
+
+    fn pow_secret(
+        &self,
+        base: &BigUint,
+        exponent: &BigUint,
+        exponent_bits: u64,
+    ) -> Option<BigUint> {
+        dispatch!(self, backend => Backend::pow_secret(backend, base, exponent, exponent_bits))
+    }
+
+    fn multi_exp(&self, terms: &[(&BigUint, &BigUint)]) -> BigUint {
+        dispatch!(self, backend => backend.multi_exp(terms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Group, ParameterSet, Prover, Verifier};
+
+    /// The test suite run against every backend, for the parameter set
+    fn suite<B: Backend>(set: ParameterSet) {
+        let group = set.group().to_backend::<B>();
+        let (p, q) = (group.p(), group.q());
+        let backend = B::new(p).unwrap();
+
+        // the arithmetic of the backend
+        let (a, b) = (group.random_scalar(), p - 1u32);
+        let product = backend.mul(&backend.to_residue(&a), &backend.to_residue(&b));
+        assert_eq!(backend.to_integer(&product), (&a * &b) % p);
+        assert_eq!(
+            backend.to_integer(&backend.to_residue(&(p + 2u32))),
+            2u32.into()
+        );
+        assert_eq!(backend.to_integer(&backend.one()), BigUint::one());
+        assert_eq!(backend.pow(&b, &a), b.modpow(&a, p));
+        assert_eq!(
+            backend.multi_exp(&[(group.g(), &a), (&b, &3u32.into())]),
+            (group.g().modpow(&a, p) * b.modpow(&3u32.into(), p)) % p
+        );
+        assert_eq!(backend.multi_exp(&[]), BigUint::one());
+        assert!(B::new(&(p + 1u32)).is_none());
+
+        // the group over the backend
+        assert_eq!(group.validate_element(group.g()), Ok(()));
+        assert!(group.validate_element(&b).is_err());
+        assert_eq!(group.exp(group.g(), &a), group.g().modpow(&a, p));
+        assert_eq!(group.exp(group.h(), &(q + 1u32)), *group.h());
+
+        // the protocol over the backend
+        let (prover, verifier) = (Prover::new(group.clone()), Verifier::new(group.clone()));
+        let x = group.random_scalar();
+        let (y1, y2) = prover.gen_public(&x);
+
+        let k = prover.gen_nonce();
+        let (r1, r2) = prover.gen_random(&k);
+        let c = verifier.request_challenge();
+        let s = prover.challenge_answer(&c, &k, &x).unwrap();
+
+        assert!(verifier.verify(&s, &c, &y1, &y2, &r1, &r2));
+        assert!(!verifier.verify(&s, &c, &y2, &y1, &r1, &r2));
+
+        let wrong = Prover::new(group.clone());
+        let k = wrong.gen_nonce();
+        let (r1, r2) = wrong.gen_random(&k);
+        let s = wrong
+            .challenge_answer(&c, &k, &group.scalar_add(&x, &1u32.into()))
+            .unwrap();
+        assert!(!verifier.verify(&s, &c, &y1, &y2, &r1, &r2));
+    }
+
+    #[test]
+    fn test_num_bigint() {
+        suite::<NumBigint>(ParameterSet::P25519);
+        suite::<NumBigint>(ParameterSet::Ffdhe2048);
+    }
+
+    #[test]
+    fn test_montgomery() {
+        suite::<Montgomery>(ParameterSet::P25519);
+        suite::<Montgomery>(ParameterSet::Ffdhe2048);
+    }
+
+    #[test]
+    fn test_fixed_width() {
+        suite::<FixedWidth256>(ParameterSet::P25519);
+        suite::<FixedWidth2048>(ParameterSet::Ffdhe2048);
+        suite::<FixedWidth3072>(ParameterSet::Ffdhe3072);
+
+        // a modulus narrower than the width
+        suite::<FixedWidth3072>(ParameterSet::Ffdhe2048);
+
+        // p is too wide for the backend - the group falls back to `modpow`
+        let group = ParameterSet::Ffdhe2048
+            .group()
+            .to_backend::<FixedWidth256>();
+        assert!(FixedWidth256::new(group.p()).is_none());
+        assert_eq!(group.validate_element(group.h()), Ok(()));
+    }
+
+    #[test]
+    fn test_field25519() {
+        suite::<Field25519>(ParameterSet::P25519);
+    }
+
+    #[test]
+    fn test_auto() {
+        suite::<Auto>(ParameterSet::P25519);
+        suite::<Auto>(ParameterSet::Ffdhe2048);
+
+        let field = ParameterSet::P25519.group().to_backend::<Auto>();
+        assert!(matches!(Auto::new(field.p()), Some(Auto::Field25519(_))));
+        let ffdhe = ParameterSet::Ffdhe2048.group().to_backend::<Auto>();
+        assert!(matches!(Auto::new(ffdhe.p()), Some(Auto::Montgomery(_))));
+    }
+
+    #[cfg(feature = "gmp")]
+    #[test]
+    fn test_gmp() {
+        suite::<Gmp>(ParameterSet::P25519);
+        suite::<Gmp>(ParameterSet::Ffdhe2048);
+    }
+}
@@ -0,0 +1,289 @@
+//! Constant-time modular arithmetic on fixed-width integers, for the operations of the Prover
+//! which involve a secret - g^x and h^x, g^k and h^k, and s = k - c * x mod q.
+//!
+//! `BigUint::modpow` (and the fixed-base tables of [`multiexp`](crate::multiexp)) take time and
+//! access memory depending on the bits of the exponent. Here the numbers are held in Montgomery
+//! form in `crypto-bigint` integers as wide as the modulus, and the exponentiation goes through
+//! every bit of the exponent (up to a public bound) with the same sequence of operations.
+//!
+//! The conversions from and to `BigUint` are not constant-time themselves (the length of a
+//! `BigUint` depends on its leading zeros), but they only reveal that much.
+
+use crypto_bigint::modular::runtime_mod::{DynResidue, DynResidueParams};
+use crypto_bigint::{Limb, Uint, Word, U1024, U2048, U256, U3072, U4096, U512, U6144, U8192};
+use num_bigint::BigUint;
+use num_traits::One;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+use crate::backend::Backend;
+use crate::{multiexp, Secret};
+
+/// An odd modulus, with its Montgomery parameters for the narrowest supported width that holds it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstantTimeModulus {
+    U256(Box<FixedWidth<{ U256::LIMBS }>>),
+    U512(Box<FixedWidth<{ U512::LIMBS }>>),
+    U1024(Box<FixedWidth<{ U1024::LIMBS }>>),
+    U2048(Box<FixedWidth<{ U2048::LIMBS }>>),
+    U3072(Box<FixedWidth<{ U3072::LIMBS }>>),
+    U4096(Box<FixedWidth<{ U4096::LIMBS }>>),
+    U6144(Box<FixedWidth<{ U6144::LIMBS }>>),
+    U8192(Box<FixedWidth<{ U8192::LIMBS }>>),
+}
+
+/// Run the expression with `$modulus` bound to the modulus of the right width
+macro_rules! dispatch {
+    ($self:expr, $modulus:ident => $expression:expr) => {
+        match $self {
+            ConstantTimeModulus::U256($modulus) => $expression,
+            ConstantTimeModulus::U512($modulus) => $expression,
+            ConstantTimeModulus::U1024($modulus) => $expression,
+            ConstantTimeModulus::U2048($modulus) => $expression,
+            ConstantTimeModulus::U3072($modulus) => $expression,
+            ConstantTimeModulus::U4096($modulus) => $expression,
+            ConstantTimeModulus::U6144($modulus) => $expression,
+            ConstantTimeModulus::U8192($modulus) => $expression,
+        }
+    };
+}
+
+impl ConstantTimeModulus {
+    /// The Montgomery parameters of the modulus, or `None` if it is even or wider than 8192 bits
+    pub fn new(modulus: &BigUint) -> Option<Self> {
+        if modulus.bit(0) {
+            Some(match modulus.bits() {
+                0..=256 => ConstantTimeModulus::U256(Box::new(FixedWidth::from_odd(modulus))),
+                257..=512 => ConstantTimeModulus::U512(Box::new(FixedWidth::from_odd(modulus))),
+                513..=1024 => ConstantTimeModulus::U1024(Box::new(FixedWidth::from_odd(modulus))),
+                1025..=2048 => ConstantTimeModulus::U2048(Box::new(FixedWidth::from_odd(modulus))),
+                2049..=3072 => ConstantTimeModulus::U3072(Box::new(FixedWidth::from_odd(modulus))),
+                3073..=4096 => ConstantTimeModulus::U4096(Box::new(FixedWidth::from_odd(modulus))),
+                4097..=6144 => ConstantTimeModulus::U6144(Box::new(FixedWidth::from_odd(modulus))),
+                6145..=8192 => ConstantTimeModulus::U8192(Box::new(FixedWidth::from_odd(modulus))),
+                _ => return None,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// base ^ exponent mod the modulus, in time which only depends on `exponent_bits` - the
+    /// exponent must be smaller than 2^exponent_bits (e.g., a scalar, reduced modulo q)
+    pub fn pow(&self, base: &BigUint, exponent: &BigUint, exponent_bits: u64) -> BigUint {
+        dispatch!(self, modulus => modulus.pow_bounded(base, exponent, exponent_bits))
+    }
+
+    /// a - b * c mod the modulus
+    pub fn mul_sub(&self, a: &BigUint, b: &BigUint, c: &BigUint) -> BigUint {
+        dispatch!(self, modulus => modulus.mul_sub(a, b, c))
+    }
+}
+
+/// The Montgomery parameters of an odd modulus of at most `LIMBS` limbs - also a [`Backend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedWidth<const LIMBS: usize> {
+    params: DynResidueParams<LIMBS>,
+    /// The width of the modulus, in bits
+    bits: u64,
+}
+
+impl<const LIMBS: usize> FixedWidth<LIMBS> {
+    const BYTES: usize = LIMBS * Limb::BYTES;
+
+    /// The modulus must be odd, and fit in `LIMBS` limbs
+    fn from_odd(modulus: &BigUint) -> Self {
+        FixedWidth {
+            params: DynResidueParams::new(&to_uint(modulus)),
+            bits: modulus.bits(),
+        }
+    }
+
+    /// The value in Montgomery form - values wider than the modulus are reduced first (which is
+    /// not constant-time, but the width of a value is not secret)
+    fn residue(&self, value: &BigUint) -> DynResidue<LIMBS> {
+        if value.bits() > self.bits {
+            let modulus = from_uint(self.params.modulus());
+            return self.residue(Secret::new(value % modulus).expose());
+        }
+
+        let mut uint = to_uint::<LIMBS>(value);
+        let residue = DynResidue::new(&uint, self.params);
+        uint.zeroize();
+        residue
+    }
+
+    fn pow_bounded(&self, base: &BigUint, exponent: &BigUint, exponent_bits: u64) -> BigUint {
+        debug_assert!(exponent.bits() <= exponent_bits);
+        let exponent_bits = exponent_bits.min(Self::BYTES as u64 * 8) as usize;
+
+        let mut exponent = to_uint::<LIMBS>(exponent);
+        let mut power = self.residue(base).pow_bounded_exp(&exponent, exponent_bits);
+        exponent.zeroize();
+
+        let result = from_uint(&power.retrieve());
+        power.zeroize();
+        result
+    }
+
+    fn mul_sub(&self, a: &BigUint, b: &BigUint, c: &BigUint) -> BigUint {
+        let (mut a, mut b, mut c) = (self.residue(a), self.residue(b), self.residue(c));
+        let mut result = a.sub(&b.mul(&c));
+
+        let value = from_uint(&result.retrieve());
+        for residue in [&mut a, &mut b, &mut c, &mut result] {
+            residue.zeroize();
+        }
+        value
+    }
+}
+
+/// Variable time, like the other backends - the exponent is not assumed to be secret
+impl<const LIMBS: usize> Backend for FixedWidth<LIMBS> {
+    type Residue = DynResidue<LIMBS>;
+
+    const NAME: &'static str = "crypto-bigint";
+
+    fn new(modulus: &BigUint) -> Option<Self> {
+        (modulus.bit(0) && !modulus.is_one() && modulus.bits() <= Self::BYTES as u64 * 8)
+            .then(|| FixedWidth::from_odd(modulus))
+    }
+
+    fn to_residue(&self, x: &BigUint) -> DynResidue<LIMBS> {
+        self.residue(x)
+    }
+
+    fn to_integer(&self, a: &DynResidue<LIMBS>) -> BigUint {
+        from_uint(&a.retrieve())
+    }
+
+    fn one(&self) -> DynResidue<LIMBS> {
+        DynResidue::one(self.params)
+    }
+
+    fn mul(&self, a: &DynResidue<LIMBS>, b: &DynResidue<LIMBS>) -> DynResidue<LIMBS> {
+        a.mul(b)
+    }
+
+    fn square(&self, a: &DynResidue<LIMBS>) -> DynResidue<LIMBS> {
+        a.square()
+    }
+
+    /// Only the significant bits of the exponent are gone through (an exponent wider than the
+    /// modulus is taken in windows by [`multiexp::multi_exp`])
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        if exponent.bits() > Self::BYTES as u64 * 8 {
+            return multiexp::multi_exp(self, &[(base, exponent)]);
+        }
+
+        self.pow_bounded(base, exponent, exponent.bits())
+    }
+}
+
+/// The value (which must fit in `LIMBS` limbs) as a fixed-width integer
+fn to_uint<const LIMBS: usize>(value: &BigUint) -> Uint<LIMBS> {
+    let width = LIMBS * Limb::BYTES;
+    let bytes = Secret::new(value.to_bytes_be());
+    assert!(bytes.expose().len() <= width, "the value is too wide");
+
+    let mut padded = vec![0u8; width];
+    padded[width - bytes.expose().len()..].copy_from_slice(bytes.expose());
+
+    Uint::from_be_slice(Secret::new(padded).expose())
+}
+
+/// The fixed-width integer as a `BigUint`
+fn from_uint<const LIMBS: usize>(value: &Uint<LIMBS>) -> BigUint {
+    let bytes: Vec<u8> = value
+        .as_words()
+        .iter()
+        .rev()
+        .flat_map(|word: &Word| word.to_be_bytes())
+        .collect();
+
+    BigUint::from_bytes_be(Secret::new(bytes).expose())
+}
+
+/// a == b, in time which only depends on `len` and the lengths of a and b - they are compared
+/// as big-endian integers padded to (at least) `len` bytes
+pub fn eq(a: &BigUint, b: &BigUint, len: usize) -> bool {
+    let (a, b) = (a.to_bytes_be(), b.to_bytes_be());
+    let len = len.max(a.len()).max(b.len());
+
+    let pad = |bytes: &[u8]| {
+        let mut padded = vec![0u8; len - bytes.len()];
+        padded.extend_from_slice(bytes);
+        padded
+    };
+
+    pad(&a).ct_eq(&pad(&b)).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::RandBigInt;
+
+    use super::*;
+    use crate::{Group, ParameterSet};
+
+    #[test]
+    fn test_new() {
+        assert!(ConstantTimeModulus::new(&BigUint::from(2u32).pow(255)).is_none());
+        assert!(ConstantTimeModulus::new(&(BigUint::from(2u32).pow(8192) + 1u32)).is_none());
+        assert!(matches!(
+            ConstantTimeModulus::new(&(BigUint::from(2u32).pow(8191) + 1u32)),
+            Some(ConstantTimeModulus::U8192(_))
+        ));
+        assert!(matches!(
+            ConstantTimeModulus::new(&BigUint::from(11u32)),
+            Some(ConstantTimeModulus::U256(_))
+        ));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut rng = rand::thread_rng();
+
+        for set in [ParameterSet::P25519, ParameterSet::Ffdhe2048] {
+            let group = set.group();
+            let (p, q) = (group.p(), group.q());
+            let (ct_p, ct_q) = (
+                ConstantTimeModulus::new(p).unwrap(),
+                ConstantTimeModulus::new(q).unwrap(),
+            );
+
+            for _ in 0..4 {
+                let base = rng.gen_biguint_below(p);
+                let exponent = rng.gen_biguint_below(q);
+                assert_eq!(
+                    ct_p.pow(&base, &exponent, q.bits()),
+                    base.modpow(&exponent, p)
+                );
+
+                let (a, b, c) = (
+                    rng.gen_biguint_below(q),
+                    rng.gen_biguint_below(q),
+                    rng.gen_biguint_below(q),
+                );
+                assert_eq!(ct_q.mul_sub(&a, &b, &c), (a + q * q - b * c) % q);
+            }
+
+            let zero = BigUint::from(0u32);
+            assert_eq!(ct_p.pow(group.g(), &zero, q.bits()), BigUint::from(1u32));
+            assert_eq!(
+                ct_p.pow(&(p + 2u32), q, q.bits()),
+                BigUint::from(2u32).modpow(q, p)
+            );
+        }
+    }
+
+    #[test]
+    fn test_eq() {
+        let (a, b) = (BigUint::from(0x1234u32), BigUint::from(0x34u32));
+
+        assert!(eq(&a, &a.clone(), 32));
+        assert!(eq(&a, &a.clone(), 0));
+        assert!(!eq(&a, &b, 32));
+        assert!(!eq(&b, &(&b << 8u32), 2));
+    }
+}
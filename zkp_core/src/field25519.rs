@@ -0,0 +1,367 @@
+//! Arithmetic modulo the Curve25519 prime, p = 2^255 - 19 - the p of the default parameter set,
+//! [`ParameterSet::P25519`](crate::ParameterSet::P25519).
+//!
+//! A number is held in five 51-bit limbs (radix 2^51), so that the product of two limbs fits in
+//! a `u128` with room to spare. Since 2^255 = 19 (mod p), the limbs of a product above 2^255 are
+//! folded back in with a multiplication by 19, instead of a division by p. Exponentiation uses a
+//! fixed 4-bit window.
+//!
+//! The limb arithmetic has no branches on the values, so the exponentiations with a secret
+//! exponent (g^x and h^x, g^k and h^k of the Prover) are also done here, in constant time (see
+//! [`Field25519::pow_secret`]).
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+use subtle::{ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroize;
+
+use crate::backend::Backend;
+use crate::multiexp::{window, WINDOW};
+use crate::Secret;
+
+/// The number of bits of a limb
+const LIMB_BITS: u64 = 51;
+
+const MASK: u64 = (1 << LIMB_BITS) - 1;
+
+/// p = 2^255 - 19
+fn p() -> BigUint {
+    (BigUint::from(1u32) << 255u32) - 19u32
+}
+
+/// A number modulo p, in five 51-bit limbs (least significant first).
+///
+/// The limbs may be slightly over 51 bits and the number may not be fully reduced, so numbers
+/// are compared by their canonical form.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldElement([u64; 5]);
+
+impl FieldElement {
+    const ZERO: FieldElement = FieldElement([0; 5]);
+    const ONE: FieldElement = FieldElement([1, 0, 0, 0, 0]);
+
+    /// Propagate the carries, so that every limb fits in 51 bits (and the number in 255 bits)
+    fn carry(self) -> FieldElement {
+        let mut limbs = self.0;
+
+        for i in 0..4 {
+            limbs[i + 1] += limbs[i] >> LIMB_BITS;
+            limbs[i] &= MASK;
+        }
+        limbs[0] += 19 * (limbs[4] >> LIMB_BITS);
+        limbs[4] &= MASK;
+
+        limbs[1] += limbs[0] >> LIMB_BITS;
+        limbs[0] &= MASK;
+
+        FieldElement(limbs)
+    }
+
+    /// The limbs of the unique representative in [0, p)
+    fn canonical(&self) -> [u64; 5] {
+        let mut limbs = self.carry().carry().0;
+
+        // the number is in [0, 2^255), so it is at least p if and only if adding 19 carries out
+        // of the top limb
+        let mut q = (limbs[0] + 19) >> LIMB_BITS;
+        for limb in &limbs[1..] {
+            q = (limb + q) >> LIMB_BITS;
+        }
+
+        limbs[0] += 19 * q;
+        for i in 0..4 {
+            limbs[i + 1] += limbs[i] >> LIMB_BITS;
+            limbs[i] &= MASK;
+        }
+        limbs[4] &= MASK;
+
+        limbs
+    }
+
+    /// a * b - every limb of the result fits in 52 bits, given that every limb of a and b does
+    fn mul(&self, other: &FieldElement) -> FieldElement {
+        let (a, b) = (&self.0, &other.0);
+        let m = |x: u64, y: u64| x as u128 * y as u128;
+
+        // the limbs of the product above 2^255 are folded back in, times 19
+        let b1_19 = 19 * b[1];
+        let b2_19 = 19 * b[2];
+        let b3_19 = 19 * b[3];
+        let b4_19 = 19 * b[4];
+
+        let c0 = m(a[0], b[0]) + m(a[1], b4_19) + m(a[2], b3_19) + m(a[3], b2_19) + m(a[4], b1_19);
+        let c1 = m(a[0], b[1]) + m(a[1], b[0]) + m(a[2], b4_19) + m(a[3], b3_19) + m(a[4], b2_19);
+        let c2 = m(a[0], b[2]) + m(a[1], b[1]) + m(a[2], b[0]) + m(a[3], b4_19) + m(a[4], b3_19);
+        let c3 = m(a[0], b[3]) + m(a[1], b[2]) + m(a[2], b[1]) + m(a[3], b[0]) + m(a[4], b4_19);
+        let c4 = m(a[0], b[4]) + m(a[1], b[3]) + m(a[2], b[2]) + m(a[3], b[1]) + m(a[4], b[0]);
+
+        reduce([c0, c1, c2, c3, c4])
+    }
+
+    /// a * a, with the symmetric terms computed once
+    fn square(&self) -> FieldElement {
+        let a = &self.0;
+        let m = |x: u64, y: u64| x as u128 * y as u128;
+
+        let a0_2 = 2 * a[0];
+        let a1_2 = 2 * a[1];
+        let a3_19 = 19 * a[3];
+        let a4_19 = 19 * a[4];
+
+        let c0 = m(a[0], a[0]) + m(a1_2, a4_19) + m(2 * a[2], a3_19);
+        let c1 = m(a0_2, a[1]) + m(2 * a[2], a4_19) + m(a[3], a3_19);
+        let c2 = m(a0_2, a[2]) + m(a[1], a[1]) + m(2 * a[3], a4_19);
+        let c3 = m(a0_2, a[3]) + m(a1_2, a[2]) + m(a[4], a4_19);
+        let c4 = m(a0_2, a[4]) + m(a1_2, a[3]) + m(a[2], a[2]);
+
+        reduce([c0, c1, c2, c3, c4])
+    }
+
+    /// The entry of the table at the index, in constant time - every entry is read, and the one
+    /// at the index is kept with a mask
+    fn select(table: &[FieldElement], index: u8) -> FieldElement {
+        table
+            .iter()
+            .enumerate()
+            .fold(FieldElement::ZERO, |selected, (j, entry)| {
+                let choice = (j as u8).ct_eq(&index);
+                FieldElement(std::array::from_fn(|i| {
+                    u64::conditional_select(&selected.0[i], &entry.0[i], choice)
+                }))
+            })
+    }
+}
+
+impl Zeroize for FieldElement {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// The 51-bit limbs of the sums of products, with the carries propagated (the carry out of the
+/// top limb is folded back into the bottom one, times 19)
+fn reduce(mut c: [u128; 5]) -> FieldElement {
+    let mut limbs = [0u64; 5];
+
+    for i in 0..4 {
+        c[i + 1] += c[i] >> LIMB_BITS;
+        limbs[i] = c[i] as u64 & MASK;
+    }
+    limbs[4] = c[4] as u64 & MASK;
+    limbs[0] += 19 * (c[4] >> LIMB_BITS) as u64;
+
+    limbs[1] += limbs[0] >> LIMB_BITS;
+    limbs[0] &= MASK;
+
+    FieldElement(limbs)
+}
+
+impl PartialEq for FieldElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+impl Eq for FieldElement {}
+
+/// The [`Backend`] for p = 2^255 - 19 (and no other modulus)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field25519 {
+    p: BigUint,
+}
+
+impl Field25519 {
+    /// The widest exponent of [`Field25519::pow_secret`]
+    const SECRET_EXPONENT_BITS: u64 = 256;
+
+    /// base ^ exponent, in time which only depends on `exponent_bits` (at most 256) - the
+    /// exponent must be smaller than 2^exponent_bits (e.g., a scalar, reduced modulo q). Every
+    /// window up to the bound is gone through, and the power of the base for a window is read
+    /// from the whole table (see `FieldElement::select`).
+    pub fn pow_secret(&self, base: &BigUint, exponent: &BigUint, exponent_bits: u64) -> BigUint {
+        assert!(exponent_bits <= Self::SECRET_EXPONENT_BITS);
+        debug_assert!(exponent.bits() <= exponent_bits);
+
+        let base = self.to_residue(base);
+
+        let mut powers = [FieldElement::ZERO; 1 << WINDOW];
+        powers[0] = FieldElement::ONE;
+        for j in 1..powers.len() {
+            powers[j] = powers[j - 1].mul(&base);
+        }
+
+        // the exponent, as fixed-width little-endian bytes
+        let mut bytes = [0u8; Self::SECRET_EXPONENT_BITS as usize / 8];
+        let le = Secret::new(exponent.to_bytes_le());
+        bytes[..le.expose().len()].copy_from_slice(le.expose());
+
+        let digit = |index: u64| {
+            (0..WINDOW).fold(0u8, |digit, bit| {
+                let i = (index * WINDOW + bit) as usize;
+                digit | ((bytes[i / 8] >> (i % 8)) & 1) << bit
+            })
+        };
+
+        let mut power =
+            (0..exponent_bits.div_ceil(WINDOW))
+                .rev()
+                .fold(FieldElement::ONE, |acc, index| {
+                    let acc = (0..WINDOW).fold(acc, |acc, _| acc.square());
+                    acc.mul(&FieldElement::select(&powers, digit(index)))
+                });
+
+        let result = self.to_integer(&power);
+        bytes.zeroize();
+        powers.zeroize();
+        power.zeroize();
+        result
+    }
+}
+
+impl Backend for Field25519 {
+    type Residue = FieldElement;
+
+    const NAME: &'static str = "field25519";
+
+    fn new(modulus: &BigUint) -> Option<Self> {
+        (*modulus == p()).then(|| Field25519 { p: p() })
+    }
+
+    fn to_residue(&self, x: &BigUint) -> FieldElement {
+        let x = x % &self.p;
+        let mut limbs = [0u64; 5];
+
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            *limb = (0..LIMB_BITS).fold(0, |limb, bit| {
+                limb | (x.bit(i as u64 * LIMB_BITS + bit) as u64) << bit
+            });
+        }
+
+        FieldElement(limbs)
+    }
+
+    fn to_integer(&self, a: &FieldElement) -> BigUint {
+        a.canonical()
+            .iter()
+            .rev()
+            .fold(BigUint::zero(), |acc, &limb| (acc << LIMB_BITS) + limb)
+    }
+
+    fn one(&self) -> FieldElement {
+        FieldElement::ONE
+    }
+
+    fn mul(&self, a: &FieldElement, b: &FieldElement) -> FieldElement {
+        a.mul(b)
+    }
+
+    fn square(&self, a: &FieldElement) -> FieldElement {
+        a.square()
+    }
+
+    /// Fixed 4-bit window - four squarings and one multiplication (by a precomputed power of
+    /// the base) for every window of the exponent
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        let base = self.to_residue(base);
+
+        let mut powers = [FieldElement::ZERO; 1 << WINDOW];
+        powers[0] = FieldElement::ONE;
+        for j in 1..powers.len() {
+            powers[j] = powers[j - 1].mul(&base);
+        }
+
+        let power =
+            (0..exponent.bits().div_ceil(WINDOW))
+                .rev()
+                .fold(FieldElement::ONE, |acc, index| {
+                    let acc = (0..WINDOW).fold(acc, |acc, _| acc.square());
+                    acc.mul(&powers[window(exponent, index)])
+                });
+
+        self.to_integer(&power)
+    }
+
+    fn pow_secret(
+        &self,
+        base: &BigUint,
+        exponent: &BigUint,
+        exponent_bits: u64,
+    ) -> Option<BigUint> {
+        (exponent_bits <= Field25519::SECRET_EXPONENT_BITS)
+            .then(|| Field25519::pow_secret(self, base, exponent, exponent_bits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn field() -> Field25519 {
+        Field25519::new(&p()).unwrap()
+    }
+
+    /// Integers of up to 384 bits - most of them larger than p
+    fn integer() -> impl Strategy<Value = BigUint> {
+        prop_oneof![
+            any::<[u8; 48]>().prop_map(|bytes| BigUint::from_bytes_le(&bytes)),
+            any::<[u8; 32]>().prop_map(|bytes| BigUint::from_bytes_le(&bytes)),
+            (0u32..64).prop_map(|offset| p() - 32u32 + offset),
+            any::<u64>().prop_map(BigUint::from),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_round_trip(x in integer()) {
+            let field = field();
+            prop_assert_eq!(field.to_integer(&field.to_residue(&x)), &x % p());
+        }
+
+        #[test]
+        fn test_mul(a in integer(), b in integer()) {
+            let field = field();
+            let (ra, rb) = (field.to_residue(&a), field.to_residue(&b));
+
+            prop_assert_eq!(field.to_integer(&field.mul(&ra, &rb)), (&a * &b) % p());
+            prop_assert_eq!(field.to_integer(&field.square(&ra)), (&a * &a) % p());
+
+            // chains of operations stay within the bounds of the limbs
+            let chained = (0..8).fold(ra, |acc, _| field.mul(&field.square(&acc), &rb));
+            let expected = (0..8).fold(&a % p(), |acc, _| (&acc * &acc * &b) % p());
+            prop_assert_eq!(field.to_integer(&chained), expected);
+        }
+
+        #[test]
+        fn test_pow(base in integer(), exponent in integer()) {
+            prop_assert_eq!(field().pow(&base, &exponent), base.modpow(&exponent, &p()));
+        }
+
+        #[test]
+        fn test_pow_secret(base in integer(), exponent in any::<[u8; 30]>(), bits in 240u64..=256) {
+            let exponent = BigUint::from_bytes_le(&exponent);
+            prop_assert_eq!(
+                field().pow_secret(&base, &exponent, bits),
+                base.modpow(&exponent, &p())
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonical() {
+        let field = field();
+
+        // p + 5 = 2^255 - 14, which fits in the limbs but is not reduced
+        let limbs = FieldElement([MASK - 13, MASK, MASK, MASK, MASK]);
+        assert_eq!(field.to_integer(&limbs), BigUint::from(5u32));
+        assert_eq!(limbs, field.to_residue(&BigUint::from(5u32)));
+        assert_eq!(field.to_integer(&field.to_residue(&p())), BigUint::zero());
+
+        assert!(Field25519::new(&(p() + 2u32)).is_none());
+        assert_eq!(
+            field.pow(&BigUint::from(2u32), &BigUint::zero()),
+            1u32.into()
+        );
+    }
+}
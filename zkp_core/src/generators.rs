@@ -0,0 +1,113 @@
+//! Verifiable ("nothing-up-my-sleeve") derivation of the generators of a [`ModPGroup`].
+//!
+//! The security of Chaum-Pedersen depends on nobody knowing log_g(h), so rather than picking
+//! `h` by hand, it is derived by hashing a public seed into the subgroup of order `q`. Anyone can
+//! recompute the generators from the seed (see [`verify_generators`]) and confirm that they were
+//! not chosen with a known relation between them.
+//!
+//! The procedure, for a seed and a label (`"g"` or `"h"`), is:
+//!
+//!   1. For counter = 0, 1, 2, ...:
+//!   2. Compute the stream SHA-256(DST || len(seed) || seed || len(label) || label || counter
+//!      || i) for i = 0, 1, ..., until it covers the bit length of p plus 128 bits (so that the
+//!      bias of the reduction below is negligible). The lengths are big-endian `u64`s, the counter
+//!      and i are big-endian `u32`s, and DST is `"zkp_chaum_pedersen/hash-to-group/v1"`.
+//!   3. Read the stream as a big-endian integer e, and compute candidate = (e mod p)^((p - 1) / q)
+//!      mod p, which lies in the subgroup of order q.
+//!   4. If candidate is neither 0 nor 1, it is the generator, otherwise try the next counter.
+
+use num_bigint::BigUint;
+use num_traits::One;
+use sha2::{Digest, Sha256};
+
+use crate::{Group, ModPGroup};
+
+/// The domain separation tag for the hash-to-group procedure
+const DST: &[u8] = b"zkp_chaum_pedersen/hash-to-group/v1";
+
+/// Derive a generator of the subgroup of order `q` of Z_p* from the seed and label
+pub fn derive_generator(p: &BigUint, q: &BigUint, seed: &[u8], label: &[u8]) -> BigUint {
+    let blocks = (p.bits() + 128).div_ceil(256) as u32;
+    let cofactor = (p - 1u32) / q;
+
+    (0u32..)
+        .map(|counter| {
+            let stream = (0..blocks)
+                .flat_map(|i| {
+                    Sha256::new()
+                        .chain_update(DST)
+                        .chain_update((seed.len() as u64).to_be_bytes())
+                        .chain_update(seed)
+                        .chain_update((label.len() as u64).to_be_bytes())
+                        .chain_update(label)
+                        .chain_update(counter.to_be_bytes())
+                        .chain_update(i.to_be_bytes())
+                        .finalize()
+                })
+                .collect::<Vec<u8>>();
+
+            (BigUint::from_bytes_be(&stream) % p).modpow(&cofactor, p)
+        })
+        .find(|candidate| *candidate > BigUint::one())
+        .unwrap()
+}
+
+/// Build the group of order `q` modulo `p`, with both generators derived from the seed
+pub fn group_from_seed(p: BigUint, q: BigUint, seed: &[u8]) -> ModPGroup {
+    let g = derive_generator(&p, &q, seed, b"g");
+    let h = derive_generator(&p, &q, seed, b"h");
+
+    ModPGroup::new(p, q, g, h)
+}
+
+/// Recompute the generators of the group from the seed, and check that they match
+pub fn verify_generators(group: &ModPGroup, seed: &[u8]) -> bool {
+    let (p, q) = (group.p(), group.q());
+
+    *group.g() == derive_generator(p, q, seed, b"g")
+        && *group.h() == derive_generator(p, q, seed, b"h")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParameterSet;
+
+    #[test]
+    fn test_derive_generator() {
+        let group = ModPGroup::default();
+        let seed = b"zkp_chaum_pedersen p25519";
+
+        // computed independently from the procedure above
+        assert_eq!(
+            derive_generator(group.p(), group.q(), seed, b"h").to_string(),
+            "54488790642068884983147094235990764803555902912311408111311842263789075379170"
+        );
+        assert_ne!(
+            derive_generator(group.p(), group.q(), seed, b"g"),
+            derive_generator(group.p(), group.q(), seed, b"h")
+        );
+    }
+
+    #[test]
+    fn test_verify_generators() {
+        for set in ParameterSet::ALL {
+            let group = set.group();
+
+            assert!(verify_generators(&group, set.seed().as_bytes()));
+            assert!(!verify_generators(&group, b"some other seed"));
+        }
+
+        let group = ModPGroup::default();
+        let tampered = ModPGroup::new(
+            group.p().clone(),
+            group.q().clone(),
+            group.g().clone(),
+            group.exp(group.g(), &BigUint::from(2u32)),
+        );
+        assert!(!verify_generators(
+            &tampered,
+            ParameterSet::default().seed().as_bytes()
+        ));
+    }
+}
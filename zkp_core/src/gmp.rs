@@ -0,0 +1,64 @@
+//! The GMP [`Backend`], through `rug` (with the `gmp` feature).
+
+use num_bigint::BigUint;
+use num_traits::One;
+use rug::integer::Order;
+use rug::Integer;
+
+use crate::backend::Backend;
+
+/// Arithmetic modulo the modulus with GMP - `mpz_powm` for the exponentiations, and a
+/// multiplication followed by a division for every product
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gmp {
+    modulus: Integer,
+}
+
+fn to_mpz(x: &BigUint) -> Integer {
+    Integer::from_digits(&x.to_u64_digits(), Order::Lsf)
+}
+
+fn to_biguint(x: &Integer) -> BigUint {
+    BigUint::from_slice(&x.to_digits::<u32>(Order::Lsf))
+}
+
+impl Backend for Gmp {
+    type Residue = Integer;
+
+    const NAME: &'static str = "gmp";
+
+    fn new(modulus: &BigUint) -> Option<Self> {
+        (modulus.bit(0) && !modulus.is_one()).then(|| Gmp {
+            modulus: to_mpz(modulus),
+        })
+    }
+
+    fn to_residue(&self, x: &BigUint) -> Integer {
+        to_mpz(x) % &self.modulus
+    }
+
+    fn to_integer(&self, a: &Integer) -> BigUint {
+        to_biguint(a)
+    }
+
+    fn one(&self) -> Integer {
+        Integer::from(1)
+    }
+
+    fn mul(&self, a: &Integer, b: &Integer) -> Integer {
+        Integer::from(a * b) % &self.modulus
+    }
+
+    fn square(&self, a: &Integer) -> Integer {
+        Integer::from(a.square_ref()) % &self.modulus
+    }
+
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        let power = Integer::from(
+            to_mpz(base)
+                .pow_mod_ref(&to_mpz(exponent), &self.modulus)
+                .expect("the exponent is not negative"),
+        );
+        to_biguint(&power)
+    }
+}
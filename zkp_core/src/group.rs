@@ -0,0 +1,112 @@
+use std::fmt::Debug;
+
+use num_bigint::BigUint;
+
+use crate::secret::Wipe;
+use crate::Error;
+
+/// A cyclic group, together with the two generators (g, h), over which the Chaum-Pedersen
+/// protocol is run.
+///
+/// Elements and scalars are exchanged between the Prover and the Verifier as strings, so every
+/// group also defines how its values are encoded on the wire.
+pub trait Group: Clone + Debug + Send + Sync + 'static {
+    /// An element of the group, such as (y1, y2) and (r1, r2)
+    type Element: Clone + Debug + PartialEq + Send + Sync;
+
+    /// An exponent (modulo the order of the group), such as x, k, c and s - the secret ones (x
+    /// and k) are wiped after use
+    type Scalar: Clone + Debug + PartialEq + Send + Sync + Wipe;
+
+    /// A short description of the group (and its parameters)
+    fn description(&self) -> String;
+
+    /// Check that the parameters describe a valid group, and that g and h are valid generators
+    fn check(&self) -> Result<(), Error>;
+
+    /// The first generator, g
+    fn g(&self) -> &Self::Element;
+
+    /// The second generator, h
+    fn h(&self) -> &Self::Element;
+
+    /// The identity element of the group
+    fn identity(&self) -> Self::Element;
+
+    /// Check that a (decoded) element received from the other party is usable in the protocol -
+    /// it must be a member of the group of prime order q, and must not be the identity
+    fn validate_element(&self, element: &Self::Element) -> Result<(), Error>;
+
+    /// base ^ exponent
+    fn exp(&self, base: &Self::Element, exponent: &Self::Scalar) -> Self::Element;
+
+    /// base ^ exponent, for a secret exponent (x or k) - in constant time, so that neither the
+    /// time taken nor the memory accessed depend on the exponent
+    fn exp_secret(&self, base: &Self::Element, exponent: &Self::Scalar) -> Self::Element;
+
+    /// The product of base_i ^ exponent_i over all the terms, computed in one pass (which is
+    /// considerably cheaper than exponentiating each base on its own)
+    fn multi_exp(&self, terms: &[(&Self::Element, &Self::Scalar)]) -> Self::Element;
+
+    /// The group operation, a . b
+    fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    /// a == b, in constant time - used by the Verifier to compare (r1, r2) to (r1', r2')
+    fn ct_eq(&self, a: &Self::Element, b: &Self::Element) -> bool;
+
+    /// The order q of the group (of g and h) - scalars are taken modulo q
+    fn order(&self) -> BigUint;
+
+    /// A uniformly random scalar, used for the nonce `k` and the challenge `c`
+    fn random_scalar(&self) -> Self::Scalar;
+
+    /// Whether the scalar is fully reduced, i.e., lies in [0, q) for a group of order q
+    fn scalar_is_reduced(&self, scalar: &Self::Scalar) -> bool;
+
+    /// a + b, in the scalar ring
+    fn scalar_add(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    /// a * b, in the scalar ring
+    fn scalar_mul(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    /// a - b, in the scalar ring
+    fn scalar_sub(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    /// a - b * c, in the scalar ring, in constant time - the answer s = k - c * x of the Prover
+    fn scalar_mul_sub(&self, a: &Self::Scalar, b: &Self::Scalar, c: &Self::Scalar) -> Self::Scalar;
+
+    /// -a, in the scalar ring
+    fn scalar_neg(&self, a: &Self::Scalar) -> Self::Scalar;
+
+    /// 1 / a, in the scalar field (the order of the group is prime), or `None` if a is zero
+    fn scalar_invert(&self, a: &Self::Scalar) -> Option<Self::Scalar>;
+
+    /// Convert the (big-endian) bytes of a non-negative integer into a scalar
+    fn scalar_from_bytes(&self, bytes: &[u8]) -> Self::Scalar;
+
+    /// The (big-endian) bytes of the scalar, the inverse of [`Group::scalar_from_bytes`] for a
+    /// reduced scalar
+    fn scalar_to_bytes(&self, scalar: &Self::Scalar) -> Vec<u8>;
+
+    /// The element in the encoding of the flavor on the wire - a decimal integer in [1, p) for
+    /// modp, and the 32-byte compressed point, hex-encoded, for Ristretto255
+    fn encode_element(&self, element: &Self::Element) -> String;
+
+    /// Decode an element encoded by [`Group::encode_element`]. This only checks the encoding: a
+    /// decimal integer for modp (of any size), and 32 hex-encoded bytes which decompress to a
+    /// point (in canonical encoding) for Ristretto255. An element received from the other party
+    /// must then be checked with [`Group::validate_element`] - for modp, that it is in [1, p),
+    /// is not the identity, and is in the subgroup of order q (every Ristretto255 point is in
+    /// the group, so only the identity is rejected).
+    fn decode_element(&self, encoded: &str) -> Result<Self::Element, Error>;
+
+    /// The scalar in the encoding of the flavor on the wire - a decimal integer for modp, and the
+    /// 32 little-endian bytes of the canonical scalar, hex-encoded, for Ristretto255
+    fn encode_scalar(&self, scalar: &Self::Scalar) -> String;
+
+    /// Decode a scalar encoded by [`Group::encode_scalar`]. Ristretto255 only accepts the
+    /// canonical encoding, reduced modulo the order of the group, but modp accepts any
+    /// non-negative decimal integer - a scalar received from the other party must then be
+    /// checked with [`Group::scalar_is_reduced`].
+    fn decode_scalar(&self, encoded: &str) -> Result<Self::Scalar, Error>;
+}
@@ -0,0 +1,279 @@
+//! Derivation of the secret `x` from the password of the user, with Argon2id (a memory-hard KDF)
+//! and a random per-user salt.
+//!
+//! The salt and the cost parameters are chosen at registration, and stored by the Verifier along
+//! with (y1, y2), so that the Prover can derive the same `x` when logging in - and so that the
+//! cost parameters of new registrations can be raised later without breaking the old ones.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+use zeroize::Zeroize;
+
+use crate::{Error, Group, Secret};
+
+/// The number of bytes of a freshly generated salt
+pub const SALT_LEN: usize = 16;
+
+/// The minimum number of bytes of a salt accepted by Argon2id
+pub const MIN_SALT_LEN: usize = argon2::MIN_SALT_LEN;
+
+/// The number of bytes derived from the password for the group - the bit length of q plus 128
+/// bits, so that reducing them into a scalar is close to uniform (as for the scalars of the test
+/// vectors, see [`crate::vectors`])
+fn output_len<G: Group>(group: &G) -> usize {
+    (group.order().bits() as usize + 128).div_ceil(8)
+}
+
+/// The Argon2id cost parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// The memory cost, in KiB
+    pub memory_kib: u32,
+    /// The number of passes over the memory
+    pub iterations: u32,
+    /// The number of lanes
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// The parameters recommended by OWASP for Argon2id (19 MiB, 2 passes, 1 lane)
+    fn default() -> Self {
+        KdfParams {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    fn argon2(&self, output_len: usize) -> Result<Argon2<'static>, Error> {
+        let params = Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(output_len),
+        )
+        .map_err(|err| Error::InvalidKdfParameters(err.to_string()))?;
+
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    /// The highest cost parameters the Prover accepts from the Verifier (256 MiB, 16 passes, 8
+    /// lanes) - higher ones would let the Verifier make the Prover allocate gigabytes
+    pub const MAX: KdfParams = KdfParams {
+        memory_kib: 256 * 1024,
+        iterations: 16,
+        parallelism: 8,
+    };
+
+    /// Check that the parameters are accepted by Argon2id
+    pub fn check(&self) -> Result<(), Error> {
+        self.argon2(Params::DEFAULT_OUTPUT_LEN).map(|_| ())
+    }
+
+    /// Check that the parameters received from the Verifier are between the defaults and
+    /// [`KdfParams::MAX`] - a malicious Verifier (or a MITM) could otherwise lower the cost, to
+    /// brute-force the password from the proof offline, or raise it to exhaust the Prover
+    pub fn check_bounds(&self) -> Result<(), Error> {
+        let (min, max) = (KdfParams::default(), KdfParams::MAX);
+
+        for (name, value, min, max) in [
+            (
+                "memory cost (KiB)",
+                self.memory_kib,
+                min.memory_kib,
+                max.memory_kib,
+            ),
+            (
+                "number of passes",
+                self.iterations,
+                min.iterations,
+                max.iterations,
+            ),
+            (
+                "number of lanes",
+                self.parallelism,
+                min.parallelism,
+                max.parallelism,
+            ),
+        ] {
+            if !(min..=max).contains(&value) {
+                return Err(Error::InvalidKdfParameters(format!(
+                    "the {name} {value} is not in the range [{min}, {max}]"
+                )));
+            }
+        }
+
+        self.check()
+    }
+}
+
+/// Generate a random salt, for a new registration
+pub fn gen_salt() -> Vec<u8> {
+    let mut salt = vec![0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+/// Derive the secret `x` from the password (any UTF-8 string), the salt of the user, and the cost
+/// parameters
+pub fn password_to_secret<G: Group>(
+    group: &G,
+    password: &Secret<String>,
+    salt: &[u8],
+    params: &KdfParams,
+) -> Result<Secret<G::Scalar>, Error> {
+    let mut output = vec![0u8; output_len(group)];
+
+    let result = params
+        .argon2(output.len())
+        .and_then(|argon2| {
+            argon2
+                .hash_password_into(password.expose().as_bytes(), salt, &mut output)
+                .map_err(|err| Error::InvalidKdfParameters(err.to_string()))
+        })
+        .map(|()| Secret::new(group.scalar_from_bytes(&output)));
+
+    output.zeroize();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ModPGroup, ParameterSet};
+
+    fn secret(password: &str) -> Secret<String> {
+        Secret::new(password.to_string())
+    }
+
+    /// Cheap parameters, so that the tests run quickly
+    const PARAMS: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_password_to_secret() {
+        let group = ModPGroup::default();
+        let salt = gen_salt();
+
+        let x = password_to_secret(
+            &group,
+            &secret("correct horse battery staple"),
+            &salt,
+            &PARAMS,
+        )
+        .unwrap();
+        assert!(group.scalar_is_reduced(x.expose()));
+        assert_eq!(
+            password_to_secret(
+                &group,
+                &secret("correct horse battery staple"),
+                &salt,
+                &PARAMS
+            ),
+            Ok(x.clone())
+        );
+
+        assert_ne!(
+            password_to_secret(
+                &group,
+                &secret("correct horse battery stapler"),
+                &salt,
+                &PARAMS
+            ),
+            Ok(x.clone())
+        );
+        assert_ne!(
+            password_to_secret(
+                &group,
+                &secret("correct horse battery staple"),
+                &gen_salt(),
+                &PARAMS
+            ),
+            Ok(x.clone())
+        );
+
+        let params = KdfParams {
+            iterations: 2,
+            ..PARAMS
+        };
+        assert_ne!(
+            password_to_secret(
+                &group,
+                &secret("correct horse battery staple"),
+                &salt,
+                &params
+            ),
+            Ok(x)
+        );
+
+        // non-numeric and non-ASCII passwords are fine
+        assert!(password_to_secret(&group, &secret("pässwörd 🔑"), &salt, &PARAMS).is_ok());
+    }
+
+    #[test]
+    fn test_large_groups() {
+        // x covers the whole range of q, not only the first 512 bits
+        let group = ParameterSet::Ffdhe3072.group();
+        let x = password_to_secret(&group, &secret("password"), &gen_salt(), &PARAMS).unwrap();
+
+        assert_eq!(output_len(&group), (3071 + 128usize).div_ceil(8));
+        assert!(x.expose().bits() > 3000);
+        assert!(group.scalar_is_reduced(x.expose()));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let group = ModPGroup::default();
+
+        // the salt is too short
+        assert!(password_to_secret(&group, &secret("password"), b"salt", &PARAMS).is_err());
+
+        let params = KdfParams {
+            memory_kib: 1,
+            ..PARAMS
+        };
+        assert!(params.check().is_err());
+        assert_eq!(KdfParams::default().check(), Ok(()));
+    }
+
+    #[test]
+    fn test_check_bounds() {
+        assert_eq!(KdfParams::default().check_bounds(), Ok(()));
+        assert_eq!(KdfParams::MAX.check_bounds(), Ok(()));
+
+        let default = KdfParams::default();
+        for params in [
+            PARAMS,
+            KdfParams {
+                memory_kib: 8,
+                ..default
+            },
+            KdfParams {
+                iterations: 1,
+                ..default
+            },
+            KdfParams {
+                memory_kib: KdfParams::MAX.memory_kib + 1,
+                ..default
+            },
+            KdfParams {
+                iterations: u32::MAX,
+                ..default
+            },
+            KdfParams {
+                parallelism: 0xff_ffff,
+                ..default
+            },
+        ] {
+            assert!(matches!(
+                params.check_bounds(),
+                Err(Error::InvalidKdfParameters(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! The core of the Chaum-Pedersen ZKP protocol, shared by the ZKP Auth client (the Prover) and the
+//! ZKP Auth server (the Verifier).
+//!
+//! The protocol is written against the [`Group`] trait so that the same [`Prover`] and
+//! [`Verifier`] can be run over different parameter sets (even several in the same process).
+
+use std::fmt;
+use std::str::FromStr;
+
+use num_bigint::{BigInt, RandomBits};
+use num_traits::Signed;
+use rand::Rng;
+
+pub mod backend;
+pub mod ct;
+pub mod field25519;
+pub mod generators;
+#[cfg(feature = "gmp")]
+pub mod gmp;
+pub mod group;
+pub mod kdf;
+pub mod modp;
+pub mod multiexp;
+pub mod params;
+pub mod primes;
+pub mod prover;
+pub mod redact;
+pub mod rfc6979;
+pub mod ristretto;
+pub mod secret;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod transcript;
+pub mod vectors;
+pub mod verifier;
+
+pub use backend::Backend;
+pub use group::Group;
+pub use kdf::KdfParams;
+pub use modp::ModPGroup;
+pub use params::{ParameterFile, ParameterSet};
+pub use prover::{Nonce, NonceGeneration, Proof, Prover};
+pub use ristretto::Ristretto255Group;
+pub use secret::Secret;
+pub use transcript::Transcript;
+pub use vectors::TestVectors;
+pub use verifier::{BatchEntry, Verifier};
+
+/// Errors raised by the ZKP core
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The value could not be decoded into a group element or scalar
+    InvalidEncoding(String),
+    /// The requested protocol flavor is not supported
+    UnknownFlavor(String),
+    /// The requested parameter set is not supported
+    UnknownParameterSet(String),
+    /// The parameter file could not be read, written or parsed, or its parameters are invalid
+    InvalidParameterFile(String),
+    /// The parameters do not describe a valid group
+    InvalidParameters(String),
+    /// The cost parameters (or the salt) are not accepted by the KDF
+    InvalidKdfParameters(String),
+    /// The value is not a valid element of the group (see [`Group::validate_element`])
+    InvalidElement(String),
+    /// The nonce has already answered another challenge (see [`prover::Nonce`])
+    NonceReuse,
+    /// The test vectors could not be read, written or parsed, or do not match this implementation
+    InvalidTestVectors(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidEncoding(what) => write!(f, "invalid encoding: {what}"),
+            Error::UnknownFlavor(name) => write!(f, "unknown protocol flavor: {name:?}"),
+            Error::UnknownParameterSet(name) => write!(f, "unknown parameter set: {name:?}"),
+            Error::InvalidParameterFile(reason) => write!(f, "invalid parameter file: {reason}"),
+            Error::InvalidParameters(reason) => write!(f, "invalid parameters: {reason}"),
+            Error::InvalidKdfParameters(reason) => write!(f, "invalid KDF parameters: {reason}"),
+            Error::InvalidElement(reason) => write!(f, "invalid group element: {reason}"),
+            Error::NonceReuse => write!(f, "the nonce has already answered another challenge"),
+            Error::InvalidTestVectors(reason) => write!(f, "invalid test vectors: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The flavors of the protocol - the client and the server of a deployment must use the same one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Flavor {
+    /// Exponentiations modulo a prime, see [`ModPGroup`]
+    #[default]
+    ModP,
+    /// Elliptic Curve cryptography, see [`Ristretto255Group`]
+    Ristretto255,
+}
+
+impl Flavor {
+    /// The environment variable from which the flavor is read
+    pub const ENV_VAR: &'static str = "ZKP_FLAVOR";
+
+    /// Read the flavor for this deployment from the environment, defaulting to [`Flavor::ModP`]
+    pub fn from_env() -> Result<Flavor, Error> {
+        std::env::var(Flavor::ENV_VAR).map_or(Ok(Flavor::default()), |name| name.parse())
+    }
+}
+
+impl FromStr for Flavor {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "modp" => Ok(Flavor::ModP),
+            "ristretto255" => Ok(Flavor::Ristretto255),
+            _ => Err(Error::UnknownFlavor(name.to_string())),
+        }
+    }
+}
+
+/// Generate a random non-negative number of (at most) `N` bits
+pub fn gen_random_with_n_bits<const N: u64>() -> BigInt {
+    let mut rng = rand::thread_rng();
+    rng.sample::<BigInt, _>(RandomBits::new(N)).abs()
+}
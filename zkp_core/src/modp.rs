@@ -0,0 +1,464 @@
+use std::sync::Arc;
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+
+use crate::backend::{Auto, Backend};
+use crate::ct::{self, ConstantTimeModulus};
+use crate::multiexp::FixedBaseTable;
+use crate::primes::{is_probable_prime, MILLER_RABIN_ROUNDS};
+use crate::{Error, Group, ParameterSet, Secret};
+
+/// The subgroup of prime order `q` of the multiplicative group of integers modulo the prime `p`,
+/// with generators `g` and `h` (both of order `q`).
+///
+/// Scalars live in Z_q, and elements and scalars are exchanged as decimal integers.
+///
+/// The arithmetic modulo p is done by the [`Backend`] `B` ([`Auto`] by default - the 51-bit limbs
+/// of [`Field25519`](crate::backend::Field25519) for p = 2^255 - 19, and Montgomery form for any
+/// other p). Since g and h are used in every exponentiation of the protocol, their powers are
+/// precomputed once, when the group is built (see [`FixedBaseTable`]). The exponentiations with a
+/// secret exponent, and the answer of the Prover, are computed in constant time instead - with the
+/// backend if it supports it ([`Field25519`](crate::backend::Field25519) does), and with
+/// [`ConstantTimeModulus`] otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModPGroup<B: Backend = Auto> {
+    p: BigUint,
+    q: BigUint,
+    g: BigUint,
+    h: BigUint,
+    /// `None` if p is even (which [`Group::check`] rejects anyway), or too wide for the backend
+    precomputed: Option<Arc<Precomputed<B>>>,
+    constant_time: Arc<ConstantTime>,
+}
+
+/// The backend for p, and the fixed-base tables for g and h
+#[derive(Debug, PartialEq, Eq)]
+struct Precomputed<B: Backend> {
+    backend: B,
+    g_table: FixedBaseTable<B>,
+    h_table: FixedBaseTable<B>,
+}
+
+/// The constant-time contexts for p and q (`None` if either is even, or wider than
+/// [`ConstantTimeModulus`] supports)
+#[derive(Debug, PartialEq, Eq)]
+struct ConstantTime {
+    p: Option<ConstantTimeModulus>,
+    q: Option<ConstantTimeModulus>,
+}
+
+impl ModPGroup {
+    pub fn new(p: BigUint, q: BigUint, g: BigUint, h: BigUint) -> Self {
+        ModPGroup::with_backend(p, q, g, h)
+    }
+}
+
+impl<B: Backend> ModPGroup<B> {
+    /// The group, with its arithmetic modulo p done by the backend `B`
+    pub fn with_backend(p: BigUint, q: BigUint, g: BigUint, h: BigUint) -> Self {
+        let precomputed = B::new(&p).map(|backend| {
+            Arc::new(Precomputed {
+                g_table: FixedBaseTable::new(&g, &backend, q.bits()),
+                h_table: FixedBaseTable::new(&h, &backend, q.bits()),
+                backend,
+            })
+        });
+
+        let constant_time = Arc::new(ConstantTime {
+            p: ConstantTimeModulus::new(&p),
+            q: ConstantTimeModulus::new(&q),
+        });
+
+        ModPGroup {
+            p,
+            q,
+            g,
+            h,
+            precomputed,
+            constant_time,
+        }
+    }
+
+    /// The same group, over another backend
+    pub fn to_backend<C: Backend>(&self) -> ModPGroup<C> {
+        ModPGroup::with_backend(
+            self.p.clone(),
+            self.q.clone(),
+            self.g.clone(),
+            self.h.clone(),
+        )
+    }
+
+    pub fn p(&self) -> &BigUint {
+        &self.p
+    }
+
+    pub fn q(&self) -> &BigUint {
+        &self.q
+    }
+
+    /// base ^ exponent, from the precomputed powers of the base, if it is one of the generators
+    /// (and the exponent is not larger than q)
+    fn fixed_base_exp(&self, base: &BigUint, exponent: &BigUint) -> Option<BigUint> {
+        let precomputed = self.precomputed.as_ref()?;
+
+        let table = if *base == self.g {
+            &precomputed.g_table
+        } else if *base == self.h {
+            &precomputed.h_table
+        } else {
+            return None;
+        };
+
+        table.exp(exponent, &precomputed.backend)
+    }
+
+    /// base ^ exponent, with the backend (or `modpow`, if there is none)
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        match &self.precomputed {
+            Some(precomputed) => precomputed.backend.pow(base, exponent),
+            None => base.modpow(exponent, &self.p),
+        }
+    }
+}
+
+impl Default for ModPGroup {
+    /// The group of the default parameter set, see [`ParameterSet::P25519`]
+    fn default() -> Self {
+        ParameterSet::default().group()
+    }
+}
+
+fn parse_decimal(encoded: &str, what: &str) -> Result<BigUint, Error> {
+    BigUint::parse_bytes(encoded.as_bytes(), 10)
+        .ok_or_else(|| Error::InvalidEncoding(format!("{what} is not a decimal integer")))
+}
+
+impl<B: Backend> Group for ModPGroup<B> {
+    type Element = BigUint;
+    type Scalar = BigUint;
+
+    fn description(&self) -> String {
+        format!("modp ({}-bit p, {}-bit q)", self.p.bits(), self.q.bits())
+    }
+
+    /// Check that p and q are prime, that q divides p - 1, and that g and h are generators of
+    /// the subgroup of order q (neither is the identity, and both have order q)
+    fn check(&self) -> Result<(), Error> {
+        let invalid = |reason: &str| Err(Error::InvalidParameters(reason.to_string()));
+
+        if !is_probable_prime(&self.p, MILLER_RABIN_ROUNDS) {
+            return invalid("p is not prime");
+        }
+
+        if !is_probable_prime(&self.q, MILLER_RABIN_ROUNDS) {
+            return invalid("q is not prime");
+        }
+
+        if !((&self.p - 1u32) % &self.q).is_zero() {
+            return invalid("q does not divide p - 1");
+        }
+
+        for (name, generator) in [("g", &self.g), ("h", &self.h)] {
+            if let Err(Error::InvalidElement(reason)) = self.validate_element(generator) {
+                return invalid(&format!("{name} is {reason}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn g(&self) -> &BigUint {
+        &self.g
+    }
+
+    fn h(&self) -> &BigUint {
+        &self.h
+    }
+
+    fn identity(&self) -> BigUint {
+        BigUint::one()
+    }
+
+    /// The element must be in the range [1, p), must not be 1, and must have order q (which rules
+    /// out p - 1, and the elements of the small subgroups of Z_p*)
+    fn validate_element(&self, element: &BigUint) -> Result<(), Error> {
+        let invalid = |reason: &str| Err(Error::InvalidElement(reason.to_string()));
+
+        if element.is_zero() || *element >= self.p {
+            return invalid("not in the range [1, p)");
+        }
+
+        if element.is_one() {
+            return invalid("the identity");
+        }
+
+        if !self.exp(element, &self.q).is_one() {
+            return invalid("not in the subgroup of order q");
+        }
+
+        Ok(())
+    }
+
+    fn exp(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        self.fixed_base_exp(base, exponent)
+            .unwrap_or_else(|| self.pow(base, exponent))
+    }
+
+    /// With the backend if it supports it (see [`Backend::pow_secret`]), or else with the
+    /// constant-time context for p - and falls back to `modpow` if there is neither
+    fn exp_secret(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        if !self.scalar_is_reduced(exponent) {
+            return self.exp_secret(base, Secret::new(exponent % &self.q).expose());
+        }
+
+        let bits = self.q.bits();
+        if let Some(power) = self
+            .precomputed
+            .as_ref()
+            .and_then(|precomputed| precomputed.backend.pow_secret(base, exponent, bits))
+        {
+            return power;
+        }
+
+        match &self.constant_time.p {
+            Some(ct_p) => ct_p.pow(base, exponent, bits),
+            None => base.modpow(exponent, &self.p),
+        }
+    }
+
+    /// The powers of g and h are looked up in their tables, and the rest are computed with the
+    /// Straus/Shamir trick (see [`Backend::multi_exp`])
+    fn multi_exp(&self, terms: &[(&BigUint, &BigUint)]) -> BigUint {
+        let Some(precomputed) = &self.precomputed else {
+            return terms
+                .iter()
+                .fold(BigUint::one(), |product, (base, exponent)| {
+                    self.mul(&product, &self.exp(base, exponent))
+                });
+        };
+
+        let mut product = BigUint::one();
+        let mut rest = Vec::with_capacity(terms.len());
+
+        for &(base, exponent) in terms {
+            match self.fixed_base_exp(base, exponent) {
+                Some(power) => product = self.mul(&product, &power),
+                None => rest.push((base, exponent)),
+            }
+        }
+
+        match rest.as_slice() {
+            [] => product,
+            [(base, exponent)] => self.mul(&product, &precomputed.backend.pow(base, exponent)),
+            _ => self.mul(&product, &precomputed.backend.multi_exp(&rest)),
+        }
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.p
+    }
+
+    /// Both elements are padded to the width of p before they are compared
+    fn ct_eq(&self, a: &BigUint, b: &BigUint) -> bool {
+        ct::eq(a, b, self.p.bits().div_ceil(8) as usize)
+    }
+
+    fn order(&self) -> BigUint {
+        self.q.clone()
+    }
+
+    /// Uniformly distributed in [0, q)
+    fn random_scalar(&self) -> BigUint {
+        rand::thread_rng().gen_biguint_below(&self.q)
+    }
+
+    fn scalar_is_reduced(&self, scalar: &BigUint) -> bool {
+        *scalar < self.q
+    }
+
+    fn scalar_add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a + b) % &self.q
+    }
+
+    fn scalar_mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.q
+    }
+
+    fn scalar_sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a % &self.q + &self.q - b % &self.q) % &self.q
+    }
+
+    /// Falls back to the (variable-time) arithmetic of `BigUint` if there is no constant-time
+    /// context for q
+    fn scalar_mul_sub(&self, a: &BigUint, b: &BigUint, c: &BigUint) -> BigUint {
+        match &self.constant_time.q {
+            Some(ct_q) => ct_q.mul_sub(a, b, c),
+            None => self.scalar_sub(a, Secret::new(self.scalar_mul(b, c)).expose()),
+        }
+    }
+
+    fn scalar_neg(&self, a: &BigUint) -> BigUint {
+        self.scalar_sub(&BigUint::zero(), a)
+    }
+
+    /// a^(q - 2) mod q, by Fermat's little theorem
+    fn scalar_invert(&self, a: &BigUint) -> Option<BigUint> {
+        let a = a % &self.q;
+        (!a.is_zero()).then(|| a.modpow(&(&self.q - 2u32), &self.q))
+    }
+
+    fn scalar_from_bytes(&self, bytes: &[u8]) -> BigUint {
+        BigUint::from_bytes_be(bytes) % &self.q
+    }
+
+    fn scalar_to_bytes(&self, scalar: &BigUint) -> Vec<u8> {
+        scalar.to_bytes_be()
+    }
+
+    fn encode_element(&self, element: &BigUint) -> String {
+        element.to_string()
+    }
+
+    fn decode_element(&self, encoded: &str) -> Result<BigUint, Error> {
+        parse_decimal(encoded, "group element")
+    }
+
+    fn encode_scalar(&self, scalar: &BigUint) -> String {
+        scalar.to_string()
+    }
+
+    fn decode_scalar(&self, encoded: &str) -> Result<BigUint, Error> {
+        parse_decimal(encoded, "scalar")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        let group = ModPGroup::default();
+
+        assert_eq!(
+            *group.p(),
+            BigUint::from(2u32).pow(255) - BigUint::from(19u32)
+        );
+        assert_eq!((group.p() - 1u32) % group.q(), BigUint::from(0u32));
+        assert_eq!(group.exp(group.g(), group.q()), BigUint::one());
+        assert_eq!(group.exp(group.h(), group.q()), BigUint::one());
+    }
+
+    #[test]
+    fn test_scalar_arithmetic() {
+        let group = ModPGroup::default();
+        let (a, b) = (BigUint::from(3u32), BigUint::from(5u32));
+
+        assert_eq!(group.scalar_sub(&a, &b), group.q() - 2u32);
+        assert_eq!(group.scalar_add(&a, &group.scalar_neg(&a)), BigUint::zero());
+        assert!(group.scalar_is_reduced(&group.random_scalar()));
+        assert!(!group.scalar_is_reduced(group.q()));
+
+        let inverse = group.scalar_invert(&a).unwrap();
+        assert_eq!(group.scalar_mul(&a, &inverse), BigUint::one());
+        assert_eq!(group.scalar_invert(group.q()), None);
+    }
+
+    #[test]
+    fn test_constant_time() {
+        for set in [ParameterSet::P25519, ParameterSet::Ffdhe2048] {
+            let group = set.group();
+            let (x, k, c) = (
+                group.random_scalar(),
+                group.random_scalar(),
+                group.random_scalar(),
+            );
+
+            assert_eq!(group.exp_secret(group.g(), &x), group.exp(group.g(), &x));
+            assert_eq!(group.exp_secret(group.h(), &k), group.exp(group.h(), &k));
+            assert_eq!(
+                group.scalar_mul_sub(&k, &c, &x),
+                group.scalar_sub(&k, &group.scalar_mul(&c, &x))
+            );
+
+            let r1 = group.exp(group.g(), &k);
+            assert!(group.ct_eq(&r1, &r1.clone()));
+            assert!(!group.ct_eq(&r1, &group.mul(&r1, group.g())));
+            assert!(!group.ct_eq(&r1, &(&r1 + group.p())));
+        }
+    }
+
+    #[test]
+    fn test_multi_exp() {
+        let group = ModPGroup::default();
+        let (a, b) = (group.random_scalar(), group.random_scalar());
+
+        assert_eq!(
+            group.multi_exp(&[(group.g(), &a), (group.h(), &b)]),
+            group.mul(&group.exp(group.g(), &a), &group.exp(group.h(), &b))
+        );
+        assert_eq!(group.multi_exp(&[]), group.identity());
+
+        let y = group.exp(group.h(), &a);
+        assert_eq!(
+            group.multi_exp(&[(group.g(), &a), (&y, &b), (group.h(), &(group.q() + 1u32))]),
+            group.g().modpow(&a, group.p()) * y.modpow(&b, group.p()) * group.h() % group.p()
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let group = ModPGroup::default();
+        assert_eq!(group.check(), Ok(()));
+
+        let (p, q) = (group.p().clone(), group.q().clone());
+        let with_generators =
+            |g: u32, h: &BigUint| ModPGroup::new(p.clone(), q.clone(), BigUint::from(g), h.clone());
+
+        // 5 has order p - 1, not q
+        assert!(with_generators(5, group.h()).check().is_err());
+        assert!(with_generators(1, group.h()).check().is_err());
+        assert!(
+            ModPGroup::new(p.clone() + 2u32, q, group.g().clone(), group.h().clone())
+                .check()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_element() {
+        let group = ModPGroup::default();
+        let p = group.p();
+
+        assert_eq!(group.validate_element(group.g()), Ok(()));
+        assert_eq!(
+            group.validate_element(&group.exp(group.h(), &group.random_scalar())),
+            Ok(())
+        );
+
+        for invalid in [
+            BigUint::zero(),
+            BigUint::one(),
+            p - 1u32,
+            p.clone(),
+            p + group.g(),
+            BigUint::from(5u32),
+        ] {
+            assert!(matches!(
+                group.validate_element(&invalid),
+                Err(Error::InvalidElement(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let group = ModPGroup::default();
+
+        assert_eq!(group.decode_element("42"), Ok(BigUint::from(42u32)));
+        assert!(group.decode_scalar("-42").is_err());
+        assert!(group.decode_scalar("forty-two").is_err());
+    }
+}
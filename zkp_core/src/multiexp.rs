@@ -0,0 +1,332 @@
+//! Exponentiation modulo a prime, faster than computing every power with `modpow` on its own.
+//!
+//!   * [`FixedBaseTable`] - for a base that is used over and over (g and h), the powers
+//!     base^(j * 2^(w * i)) are precomputed once, so that an exponentiation only takes one
+//!     multiplication per w-bit window of the exponent (and no squarings at all).
+//!   * [`multi_exp`] - for a product of powers, base_1^e_1 . base_2^e_2 ..., the squarings are
+//!     shared by all the bases (Straus/Shamir trick), and every base only contributes one
+//!     multiplication per w-bit window of its exponent.
+//!
+//! Both are written against a [`Backend`]. The default one, [`Montgomery`], does all the
+//! multiplications in Montgomery form (as `modpow` does internally), which avoids a division for
+//! every reduction modulo p.
+
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::backend::Backend;
+
+/// The number of bits of the exponent consumed at a time
+pub const WINDOW: u64 = 4;
+
+/// The w-bit window of the exponent at the given index (counting from the least significant)
+pub(crate) fn window(exponent: &BigUint, index: u64) -> usize {
+    (0..WINDOW).fold(0, |digit, bit| {
+        digit | (exponent.bit(index * WINDOW + bit) as usize) << bit
+    })
+}
+
+/// Arithmetic modulo an odd modulus p, on numbers in Montgomery form (x . R mod p, with
+/// R = 2^(64 * n) for a modulus of n 64-bit limbs)
+#[derive(Clone, PartialEq, Eq)]
+pub struct Montgomery {
+    /// the limbs of p, least significant first
+    modulus: Vec<u64>,
+    /// -p^-1 mod 2^64
+    inverse: u64,
+    /// R^2 mod p, for converting into Montgomery form
+    r_squared: Vec<u64>,
+    /// R mod p, i.e., 1 in Montgomery form
+    one: Vec<u64>,
+}
+
+impl Montgomery {
+    /// The Montgomery context for the modulus, or `None` if it is even (or 1)
+    pub fn new(modulus: &BigUint) -> Option<Self> {
+        if !modulus.bit(0) || modulus.is_one() {
+            return None;
+        }
+
+        let limbs = modulus.to_u64_digits();
+        let r = BigUint::one() << (64 * limbs.len());
+
+        // Newton's iteration doubles the number of correct low bits of p^-1 every time
+        let inverse = (0..6).fold(1u64, |inverse, _| {
+            inverse.wrapping_mul(2u64.wrapping_sub(limbs[0].wrapping_mul(inverse)))
+        });
+
+        Some(Montgomery {
+            r_squared: to_limbs(&((&r * &r) % modulus), limbs.len()),
+            one: to_limbs(&(r % modulus), limbs.len()),
+            inverse: inverse.wrapping_neg(),
+            modulus: limbs,
+        })
+    }
+
+    /// Convert x (any non-negative integer) into Montgomery form
+    pub fn to_montgomery(&self, x: &BigUint) -> Vec<u64> {
+        let x = x % from_limbs(&self.modulus);
+        self.mul(&to_limbs(&x, self.modulus.len()), &self.r_squared)
+    }
+
+    /// Convert out of Montgomery form
+    pub fn from_montgomery(&self, a: &[u64]) -> BigUint {
+        let mut one = vec![0; self.modulus.len()];
+        one[0] = 1;
+        from_limbs(&self.mul(a, &one))
+    }
+
+    /// a . b . R^-1 mod p, with the CIOS (coarsely integrated operand scanning) method
+    pub fn mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let (p, n) = (&self.modulus, self.modulus.len());
+        let mut t = vec![0u64; n + 2];
+
+        for &b_i in b {
+            let mut carry = 0u64;
+            for j in 0..n {
+                (t[j], carry) = mul_add(a[j], b_i, t[j], carry);
+            }
+            let (sum, overflow) = t[n].overflowing_add(carry);
+            (t[n], t[n + 1]) = (sum, overflow as u64);
+
+            let m = t[0].wrapping_mul(self.inverse);
+            let (_, mut carry) = mul_add(m, p[0], t[0], 0);
+            for j in 1..n {
+                (t[j - 1], carry) = mul_add(m, p[j], t[j], carry);
+            }
+            let (sum, overflow) = t[n].overflowing_add(carry);
+            (t[n - 1], t[n]) = (sum, t[n + 1] + overflow as u64);
+        }
+
+        if t[n] != 0 || !less_than(&t[..n], p) {
+            let mut borrow = false;
+            for j in 0..n {
+                let (diff, borrow_1) = t[j].overflowing_sub(p[j]);
+                let (diff, borrow_2) = diff.overflowing_sub(borrow as u64);
+                (t[j], borrow) = (diff, borrow_1 || borrow_2);
+            }
+        }
+
+        t.truncate(n);
+        t
+    }
+}
+
+impl Backend for Montgomery {
+    type Residue = Vec<u64>;
+
+    const NAME: &'static str = "montgomery";
+
+    fn new(modulus: &BigUint) -> Option<Self> {
+        Montgomery::new(modulus)
+    }
+
+    fn to_residue(&self, x: &BigUint) -> Vec<u64> {
+        self.to_montgomery(x)
+    }
+
+    fn to_integer(&self, a: &Vec<u64>) -> BigUint {
+        self.from_montgomery(a)
+    }
+
+    fn one(&self) -> Vec<u64> {
+        self.one.clone()
+    }
+
+    fn mul(&self, a: &Vec<u64>, b: &Vec<u64>) -> Vec<u64> {
+        Montgomery::mul(self, a, b)
+    }
+
+    /// A single power is faster with `modpow` (which has a wider window)
+    fn pow(&self, base: &BigUint, exponent: &BigUint) -> BigUint {
+        base.modpow(exponent, &from_limbs(&self.modulus))
+    }
+}
+
+impl fmt::Debug for Montgomery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Montgomery")
+            .field("limbs", &self.modulus.len())
+            .finish()
+    }
+}
+
+/// a * b + c + d, as (low, high) 64-bit words (which cannot overflow)
+fn mul_add(a: u64, b: u64, c: u64, d: u64) -> (u64, u64) {
+    let wide = a as u128 * b as u128 + c as u128 + d as u128;
+    (wide as u64, (wide >> 64) as u64)
+}
+
+/// Whether a < b, for numbers of the same number of limbs
+fn less_than(a: &[u64], b: &[u64]) -> bool {
+    a.iter().rev().cmp(b.iter().rev()).is_lt()
+}
+
+fn to_limbs(x: &BigUint, n: usize) -> Vec<u64> {
+    let mut limbs = x.to_u64_digits();
+    limbs.resize(n, 0);
+    limbs
+}
+
+fn from_limbs(limbs: &[u64]) -> BigUint {
+    limbs
+        .iter()
+        .rev()
+        .fold(BigUint::zero(), |acc, &limb| (acc << 64) + limb)
+}
+
+/// The product of base_i ^ exponent_i mod p over all the terms, with the squarings shared
+/// (Straus/Shamir trick)
+pub fn multi_exp<B: Backend>(backend: &B, terms: &[(&BigUint, &BigUint)]) -> BigUint {
+    let windows = terms
+        .iter()
+        .map(|(_, exponent)| exponent.bits().div_ceil(WINDOW))
+        .max()
+        .unwrap_or(0);
+
+    // powers[t][j] = base_t^j, in the representation of the backend
+    let powers = terms
+        .iter()
+        .map(|(base, _)| {
+            let base = backend.to_residue(base);
+            let mut row = vec![backend.one(), base.clone()];
+
+            for j in 2..(1 << WINDOW) {
+                row.push(backend.mul(&row[j - 1], &base));
+            }
+
+            row
+        })
+        .collect::<Vec<_>>();
+
+    let product = (0..windows).rev().fold(backend.one(), |acc, index| {
+        let acc = (0..WINDOW).fold(acc, |acc, _| backend.square(&acc));
+
+        terms
+            .iter()
+            .zip(&powers)
+            .map(|((_, exponent), row)| (window(exponent, index), row))
+            .filter(|(digit, _)| *digit != 0)
+            .fold(acc, |acc, (digit, row)| backend.mul(&acc, &row[digit]))
+    });
+
+    backend.to_integer(&product)
+}
+
+/// Precomputed powers of a fixed base, for exponents of up to a given number of bits
+#[derive(Clone, PartialEq, Eq)]
+pub struct FixedBaseTable<B: Backend> {
+    /// table[i][j] = base^(j * 2^(WINDOW * i)), in the representation of the backend
+    table: Vec<Vec<B::Residue>>,
+}
+
+impl<B: Backend> FixedBaseTable<B> {
+    pub fn new(base: &BigUint, backend: &B, bits: u64) -> Self {
+        let mut table = Vec::with_capacity(bits.div_ceil(WINDOW) as usize);
+        let mut power = backend.to_residue(base);
+
+        for _ in 0..bits.div_ceil(WINDOW) {
+            let mut row = Vec::with_capacity(1 << WINDOW);
+            row.push(backend.one());
+
+            for j in 1..(1 << WINDOW) {
+                row.push(backend.mul(&row[j - 1], &power));
+            }
+
+            power = backend.mul(&row[(1 << WINDOW) - 1], &power);
+            table.push(row);
+        }
+
+        FixedBaseTable { table }
+    }
+
+    /// base ^ exponent mod p, or `None` if the exponent has more bits than the table covers
+    pub fn exp(&self, exponent: &BigUint, backend: &B) -> Option<BigUint> {
+        if exponent.bits() > self.table.len() as u64 * WINDOW {
+            return None;
+        }
+
+        let product = self
+            .table
+            .iter()
+            .enumerate()
+            .map(|(i, row)| (window(exponent, i as u64), row))
+            .filter(|(digit, _)| *digit != 0)
+            .fold(backend.one(), |acc, (digit, row)| {
+                backend.mul(&acc, &row[digit])
+            });
+
+        Some(backend.to_integer(&product))
+    }
+}
+
+impl<B: Backend> fmt::Debug for FixedBaseTable<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FixedBaseTable")
+            .field("bits", &(self.table.len() as u64 * WINDOW))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Group, ModPGroup, ParameterSet};
+
+    #[test]
+    fn test_montgomery() {
+        for set in [ParameterSet::P25519, ParameterSet::Ffdhe2048] {
+            let group = set.group();
+            let (p, montgomery) = (group.p(), Montgomery::new(group.p()).unwrap());
+            let (a, b) = (group.random_scalar(), p - 1u32);
+
+            let product =
+                montgomery.mul(&montgomery.to_montgomery(&a), &montgomery.to_montgomery(&b));
+            assert_eq!(montgomery.from_montgomery(&product), (&a * &b) % p);
+            assert_eq!(montgomery.from_montgomery(&montgomery.to_montgomery(&b)), b);
+        }
+
+        assert!(Montgomery::new(&BigUint::from(1u32 << 20)).is_none());
+    }
+
+    #[test]
+    fn test_fixed_base_table() {
+        let group = ModPGroup::default();
+        let montgomery = Montgomery::new(group.p()).unwrap();
+        let table = FixedBaseTable::new(group.g(), &montgomery, group.q().bits());
+
+        for exponent in [
+            BigUint::from(0u32),
+            BigUint::from(1u32),
+            group.q() - 1u32,
+            group.random_scalar(),
+        ] {
+            assert_eq!(
+                table.exp(&exponent, &montgomery),
+                Some(group.g().modpow(&exponent, group.p()))
+            );
+        }
+
+        assert_eq!(table.exp(&(group.q() << 8u32), &montgomery), None);
+    }
+
+    #[test]
+    fn test_multi_exp() {
+        let group = ModPGroup::default();
+        let (p, montgomery) = (group.p(), Montgomery::new(group.p()).unwrap());
+
+        let (y, a, b) = (
+            group.random_scalar(),
+            group.random_scalar(),
+            BigUint::from(3u32),
+        );
+
+        assert_eq!(
+            multi_exp(&montgomery, &[(group.g(), &a), (&y, &b)]),
+            (group.g().modpow(&a, p) * y.modpow(&b, p)) % p
+        );
+        assert_eq!(multi_exp(&montgomery, &[]), BigUint::one());
+    }
+}
@@ -0,0 +1,298 @@
+//! Named parameter sets for the modp flavor, so that a deployment can run over a known (and
+//! reviewed) group rather than hand-picked values.
+//!
+//! Apart from the default, all of them are safe primes, p = 2q + 1, taken from the RFCs. For every
+//! set, both generators are derived from a public seed (see [`crate::generators`]), so that they
+//! can be audited.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::generators::{group_from_seed, verify_generators};
+use crate::{Error, Group, ModPGroup};
+
+/// The order of the prime-order subgroup used with the default prime, p = 2^255 - 19, where
+/// p - 1 = 2^2 * 3 * 65147 * q
+const P25519_Q: &str = "74058212732561358302231226437062788676166966415465897661863160754340907";
+
+/// RFC 7919, Appendix A.1
+const FFDHE2048_P: &str = concat!(
+    "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695",
+    "A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617A",
+    "D3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935",
+    "984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797A",
+    "BC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4",
+    "AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F61",
+    "9172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005",
+    "C58EF1837D1683B2C6F34A26C1B2EFFA886B423861285C97FFFFFFFFFFFFFFFF",
+);
+
+/// RFC 7919, Appendix A.2
+const FFDHE3072_P: &str = concat!(
+    "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695",
+    "A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617A",
+    "D3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935",
+    "984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797A",
+    "BC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4",
+    "AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F61",
+    "9172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005",
+    "C58EF1837D1683B2C6F34A26C1B2EFFA886B4238611FCFDCDE355B3B6519035B",
+    "BC34F4DEF99C023861B46FC9D6E6C9077AD91D2691F7F7EE598CB0FAC186D91C",
+    "AEFE130985139270B4130C93BC437944F4FD4452E2D74DD364F2E21E71F54BFF",
+    "5CAE82AB9C9DF69EE86D2BC522363A0DABC521979B0DEADA1DBF9A42D5C4484E",
+    "0ABCD06BFA53DDEF3C1B20EE3FD59D7C25E41D2B66C62E37FFFFFFFFFFFFFFFF",
+);
+
+/// RFC 3526, Section 3 (the 2048-bit MODP Group)
+const MODP2048_P: &str = concat!(
+    "FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74",
+    "020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437",
+    "4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7ED",
+    "EE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF05",
+    "98DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB",
+    "9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3B",
+    "E39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF695581718",
+    "3995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF",
+);
+
+/// The parameter sets available to the modp flavor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParameterSet {
+    /// p = 2^255 - 19 (Curve25519), with a 236-bit subgroup order
+    #[default]
+    P25519,
+    /// The 2048-bit finite field Diffie-Hellman group of RFC 7919
+    Ffdhe2048,
+    /// The 3072-bit finite field Diffie-Hellman group of RFC 7919
+    Ffdhe3072,
+    /// The 2048-bit MODP group of RFC 3526
+    Modp2048,
+}
+
+impl ParameterSet {
+    /// The environment variable from which the parameter set is read
+    pub const ENV_VAR: &'static str = "ZKP_PARAMS";
+
+    pub const ALL: [ParameterSet; 4] = [
+        ParameterSet::P25519,
+        ParameterSet::Ffdhe2048,
+        ParameterSet::Ffdhe3072,
+        ParameterSet::Modp2048,
+    ];
+
+    /// Read the parameter set for this deployment from the environment, defaulting to
+    /// [`ParameterSet::P25519`]
+    pub fn from_env() -> Result<ParameterSet, Error> {
+        std::env::var(ParameterSet::ENV_VAR)
+            .map_or(Ok(ParameterSet::default()), |name| name.parse())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParameterSet::P25519 => "p25519",
+            ParameterSet::Ffdhe2048 => "ffdhe2048",
+            ParameterSet::Ffdhe3072 => "ffdhe3072",
+            ParameterSet::Modp2048 => "modp2048",
+        }
+    }
+
+    /// The public seed from which the generators of this parameter set are derived
+    pub fn seed(&self) -> &'static str {
+        match self {
+            ParameterSet::P25519 => "zkp_chaum_pedersen p25519",
+            ParameterSet::Ffdhe2048 => "zkp_chaum_pedersen ffdhe2048",
+            ParameterSet::Ffdhe3072 => "zkp_chaum_pedersen ffdhe3072",
+            ParameterSet::Modp2048 => "zkp_chaum_pedersen modp2048",
+        }
+    }
+
+    /// The group for this parameter set
+    pub fn group(&self) -> ModPGroup {
+        let (p, q) = match self {
+            ParameterSet::P25519 => (
+                BigUint::from(2u32).pow(255) - BigUint::from(19u32),
+                BigUint::parse_bytes(P25519_Q.as_bytes(), 10).unwrap(),
+            ),
+            ParameterSet::Ffdhe2048 => safe_prime(FFDHE2048_P),
+            ParameterSet::Ffdhe3072 => safe_prime(FFDHE3072_P),
+            ParameterSet::Modp2048 => safe_prime(MODP2048_P),
+        };
+
+        group_from_seed(p, q, self.seed().as_bytes())
+    }
+}
+
+/// (p, q) for the (hex-encoded) safe prime p = 2q + 1
+fn safe_prime(p: &str) -> (BigUint, BigUint) {
+    let p = BigUint::parse_bytes(p.as_bytes(), 16).unwrap();
+    let q = (&p - 1u32) / 2u32;
+
+    (p, q)
+}
+
+impl fmt::Display for ParameterSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for ParameterSet {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        ParameterSet::ALL
+            .into_iter()
+            .find(|set| set.name() == name)
+            .ok_or_else(|| Error::UnknownParameterSet(name.to_string()))
+    }
+}
+
+/// A custom parameter set, as written by `zkp-paramgen`, with the values hex-encoded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParameterFile {
+    pub name: String,
+    /// The public seed from which g and h are derived
+    pub seed: String,
+    pub p: String,
+    pub q: String,
+    pub g: String,
+    pub h: String,
+}
+
+impl ParameterFile {
+    /// The environment variable from which the path to the parameter file is read
+    pub const ENV_VAR: &'static str = "ZKP_PARAMS_FILE";
+
+    pub fn new(name: &str, seed: &str, group: &ModPGroup) -> Self {
+        ParameterFile {
+            name: name.to_string(),
+            seed: seed.to_string(),
+            p: group.p().to_str_radix(16),
+            q: group.q().to_str_radix(16),
+            g: group.g().to_str_radix(16),
+            h: group.h().to_str_radix(16),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<ParameterFile, Error> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|err| {
+            Error::InvalidParameterFile(format!("failed to read {}: {err}", path.display()))
+        })?;
+
+        serde_json::from_str(&contents).map_err(|err| {
+            Error::InvalidParameterFile(format!("failed to parse {}: {err}", path.display()))
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let contents = serde_json::to_string_pretty(self).unwrap();
+
+        std::fs::write(path, contents + "\n").map_err(|err| {
+            Error::InvalidParameterFile(format!("failed to write {}: {err}", path.display()))
+        })
+    }
+
+    /// The group for these parameters, after checking that the generators were derived from the
+    /// seed, and that the parameters describe a valid group (see [`Group::check`])
+    pub fn group(&self) -> Result<ModPGroup, Error> {
+        let parse = |value: &str, what: &str| {
+            BigUint::parse_bytes(value.as_bytes(), 16)
+                .ok_or_else(|| Error::InvalidParameterFile(format!("{what} is not a hex integer")))
+        };
+
+        let group = ModPGroup::new(
+            parse(&self.p, "p")?,
+            parse(&self.q, "q")?,
+            parse(&self.g, "g")?,
+            parse(&self.h, "h")?,
+        );
+
+        if !verify_generators(&group, self.seed.as_bytes()) {
+            return Err(Error::InvalidParameterFile(format!(
+                "the generators of {:?} were not derived from its seed",
+                self.name
+            )));
+        }
+
+        group.check().map_err(|err| {
+            Error::InvalidParameterFile(format!(
+                "the parameters of {:?} are invalid: {err}",
+                self.name
+            ))
+        })?;
+
+        Ok(group)
+    }
+}
+
+/// The modp group for this deployment - from the parameter file in `ZKP_PARAMS_FILE` if it is
+/// set, or else the named parameter set in `ZKP_PARAMS`
+pub fn group_from_env() -> Result<ModPGroup, Error> {
+    match std::env::var(ParameterFile::ENV_VAR) {
+        Ok(path) => ParameterFile::load(path)?.group(),
+        Err(_) => Ok(ParameterSet::from_env()?.group()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::One;
+
+    use super::*;
+    use crate::Group;
+
+    #[test]
+    fn test_parameter_sets() {
+        for set in ParameterSet::ALL {
+            let group = set.group();
+
+            assert_eq!(set.name().parse(), Ok(set));
+            assert_eq!((group.p() - 1u32) % group.q(), BigUint::from(0u32));
+
+            for generator in [group.g(), group.h()] {
+                assert_ne!(*generator, BigUint::one());
+                assert_eq!(group.exp(generator, group.q()), BigUint::one());
+            }
+        }
+    }
+
+    #[test]
+    fn test_parameter_file() {
+        let set = ParameterSet::P25519;
+        let path = std::env::temp_dir().join("zkp_core_test_parameter_file.json");
+
+        let file = ParameterFile::new(set.name(), set.seed(), &set.group());
+        file.save(&path).unwrap();
+
+        let loaded = ParameterFile::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, file);
+        assert_eq!(loaded.group(), Ok(set.group()));
+
+        let tampered = ParameterFile {
+            h: "9".into(),
+            ..file.clone()
+        };
+        assert!(tampered.group().is_err());
+
+        // a composite p, with the generators derived from the seed for it
+        let group = set.group();
+        let tampered = group_from_seed(group.p() + 2u32, group.q().clone(), set.seed().as_bytes());
+        assert!(matches!(
+            ParameterFile::new(set.name(), set.seed(), &tampered).group(),
+            Err(Error::InvalidParameterFile(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_parameter_set() {
+        assert!("modp1024".parse::<ParameterSet>().is_err());
+    }
+}
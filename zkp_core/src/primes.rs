@@ -0,0 +1,134 @@
+//! Primality testing and safe prime generation, for checking and generating parameter sets
+
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+
+/// The number of Miller-Rabin rounds used by default (a false positive has a probability of at
+/// most 4^-64)
+pub const MILLER_RABIN_ROUNDS: usize = 64;
+
+/// The odd primes below 1000, used to sieve candidates before running Miller-Rabin
+fn small_primes() -> impl Iterator<Item = u32> {
+    (3u32..1000).step_by(2).filter(|n| {
+        (3..)
+            .step_by(2)
+            .take_while(|d| d * d <= *n)
+            .all(|d| n % d != 0)
+    })
+}
+
+/// Miller-Rabin probabilistic primality test, with `rounds` random bases
+pub fn is_probable_prime(n: &BigUint, rounds: usize) -> bool {
+    let two = BigUint::from(2u32);
+
+    if *n < two {
+        return false;
+    }
+
+    for prime in std::iter::once(2).chain(small_primes()) {
+        if (n % prime).is_zero() {
+            return *n == BigUint::from(prime);
+        }
+    }
+
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap();
+    let d = &n_minus_one >> s;
+
+    let mut rng = rand::thread_rng();
+
+    'witness: for _ in 0..rounds {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+/// Generate a random safe prime p = 2q + 1 (with q prime) of exactly `bits` bits, returning
+/// (p, q)
+pub fn gen_safe_prime(bits: u64) -> (BigUint, BigUint) {
+    assert!(bits >= 16, "safe primes need at least 16 bits");
+
+    let primes = small_primes().collect::<Vec<_>>();
+    let mut rng = rand::thread_rng();
+
+    loop {
+        // q has exactly (bits - 1) bits, and is odd
+        let mut q = rng.gen_biguint(bits - 1);
+        q.set_bit(bits - 2, true);
+        q.set_bit(0, true);
+
+        // neither q nor p = 2q + 1 may have a small factor r, i.e., q mod r must be neither 0
+        // nor (r - 1) / 2
+        let sieved = primes.iter().all(|&r| {
+            let residue = (&q % r).to_u32().unwrap();
+            residue != 0 && residue != (r - 1) / 2
+        });
+
+        if !sieved {
+            continue;
+        }
+
+        let p = &q * 2u32 + 1u32;
+
+        if is_probable_prime(&q, MILLER_RABIN_ROUNDS) && is_probable_prime(&p, MILLER_RABIN_ROUNDS)
+        {
+            return (p, q);
+        }
+    }
+}
+
+/// Whether `n` is a safe prime, i.e., both n and (n - 1) / 2 are prime
+pub fn is_safe_prime(n: &BigUint) -> bool {
+    n.is_odd()
+        && is_probable_prime(n, MILLER_RABIN_ROUNDS)
+        && is_probable_prime(&(n >> 1u32), MILLER_RABIN_ROUNDS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ParameterSet;
+
+    #[test]
+    fn test_is_probable_prime() {
+        let primes = [2u32, 3, 5, 997, 65147, 2147483647];
+        let composites = [0u32, 1, 4, 561, 1_000_000, 2147483649];
+
+        for n in primes {
+            assert!(is_probable_prime(&BigUint::from(n), MILLER_RABIN_ROUNDS));
+        }
+
+        for n in composites {
+            assert!(!is_probable_prime(&BigUint::from(n), MILLER_RABIN_ROUNDS));
+        }
+
+        let group = ParameterSet::P25519.group();
+        assert!(is_probable_prime(group.p(), MILLER_RABIN_ROUNDS));
+        assert!(is_probable_prime(group.q(), MILLER_RABIN_ROUNDS));
+    }
+
+    #[test]
+    fn test_gen_safe_prime() {
+        let (p, q) = gen_safe_prime(64);
+
+        assert_eq!(p.bits(), 64);
+        assert_eq!(p, &q * 2u32 + 1u32);
+        assert!(is_safe_prime(&p));
+    }
+}
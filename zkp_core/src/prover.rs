@@ -0,0 +1,260 @@
+use std::sync::OnceLock;
+
+use num_bigint::BigUint;
+use rand::RngCore;
+use tracing::debug;
+
+use crate::transcript::Transcript;
+use crate::{rfc6979, Error, Group, Secret};
+
+/// A non-interactive Chaum-Pedersen proof, with the challenge derived from a [`Transcript`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proof<G: Group> {
+    pub r1: G::Element,
+    pub r2: G::Element,
+    pub s: G::Scalar,
+}
+
+/// The random `k` of a single run of the protocol, behind the commitment (r1, r2).
+///
+/// Two answers with the same `k` to different challenges, s1 = k - c1 * x and s2 = k - c2 * x,
+/// give the secret away: x = (s1 - s2) / (c2 - c1). So a nonce cannot be cloned, and
+/// [`Prover::challenge_answer`] refuses to answer a second challenge with it. It is wiped when
+/// dropped.
+#[derive(Debug)]
+pub struct Nonce<G: Group> {
+    k: Secret<G::Scalar>,
+    /// the challenge answered with this nonce, if any
+    challenge: OnceLock<G::Scalar>,
+}
+
+impl<G: Group> Nonce<G> {
+    /// The nonce for a known `k`, as given by the test vectors (see [`crate::vectors`])
+    pub(crate) fn from_scalar(k: G::Scalar) -> Self {
+        Nonce {
+            k: Secret::new(k),
+            challenge: OnceLock::new(),
+        }
+    }
+}
+
+/// How the Prover draws the nonces of its non-interactive proofs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonceGeneration {
+    /// `k` is drawn from the system RNG
+    #[default]
+    Random,
+    /// `k` is derived from `x`, the transcript of the proof and fresh randomness, with
+    /// HMAC-DRBG (see [`rfc6979`]) - a weak or repeating RNG does not leak `x`
+    Derived,
+}
+
+/// The ZKP Chaum-Pedersen Prover
+#[derive(Debug, Clone)]
+pub struct Prover<G: Group> {
+    group: G,
+    nonce_generation: NonceGeneration,
+}
+
+impl<G: Group> Prover<G> {
+    pub fn new(group: G) -> Self {
+        Prover {
+            group,
+            nonce_generation: NonceGeneration::default(),
+        }
+    }
+
+    pub fn with_nonce_generation(mut self, nonce_generation: NonceGeneration) -> Self {
+        self.nonce_generation = nonce_generation;
+        self
+    }
+
+    pub fn group(&self) -> &G {
+        &self.group
+    }
+
+    /// Generate the public data for the Chaum-Pedersen protocol - (y1, y2)
+    pub fn gen_public(&self, x: &G::Scalar) -> (G::Element, G::Element) {
+        (
+            self.group.exp_secret(self.group.g(), x),
+            self.group.exp_secret(self.group.h(), x),
+        )
+    }
+
+    /// Generate the random `k` (uniformly in [0, q)) used for the challenge request parameters,
+    /// (r1, r2)
+    pub fn gen_nonce(&self) -> Nonce<G> {
+        Nonce {
+            k: Secret::new(self.group.random_scalar()),
+            challenge: OnceLock::new(),
+        }
+    }
+
+    /// Derive `k` from the secret, the context of the proof (e.g., the digest of its transcript,
+    /// which binds the user), and 32 bytes of fresh randomness, in the style of RFC 6979
+    pub fn derive_nonce(&self, x: &G::Scalar, context: &[u8]) -> Nonce<G> {
+        let mut randomness = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut randomness);
+
+        self.derive_nonce_with(x, context, &randomness)
+    }
+
+    /// Derive `k` with the given randomness (none at all, for the deterministic nonces of
+    /// RFC 6979)
+    pub fn derive_nonce_with(&self, x: &G::Scalar, context: &[u8], randomness: &[u8]) -> Nonce<G> {
+        let x = Secret::new(self.group.scalar_to_bytes(x));
+        let x = Secret::new(BigUint::from_bytes_be(x.expose()));
+
+        let k = Secret::new(rfc6979::generate_k(
+            &self.group.order(),
+            x.expose(),
+            context,
+            randomness,
+        ));
+        let k = Secret::new(k.expose().to_bytes_be());
+
+        Nonce {
+            k: Secret::new(self.group.scalar_from_bytes(k.expose())),
+            challenge: OnceLock::new(),
+        }
+    }
+
+    /// Generate the challenge request parameters, (r1, r2), for the given `k`
+    pub fn gen_random(&self, k: &Nonce<G>) -> (G::Element, G::Element) {
+        (
+            self.group.exp_secret(self.group.g(), k.k.expose()),
+            self.group.exp_secret(self.group.h(), k.k.expose()),
+        )
+    }
+
+    /// Generate `s`, the challenge answer (s = k - c * x mod q) - or fail if `k` has already
+    /// answered another challenge
+    pub fn challenge_answer(
+        &self,
+        c: &G::Scalar,
+        k: &Nonce<G>,
+        x: &G::Scalar,
+    ) -> Result<G::Scalar, Error> {
+        if k.challenge.get_or_init(|| c.clone()) != c {
+            return Err(Error::NonceReuse);
+        }
+
+        Ok(self.answer(c, k.k.expose(), x))
+    }
+
+    fn answer(&self, c: &G::Scalar, k: &G::Scalar, x: &G::Scalar) -> G::Scalar {
+        debug!("c = {c:?}");
+        self.group.scalar_mul_sub(k, c, x)
+    }
+
+    /// Generate a non-interactive proof of knowledge of `x` (for its public data, (y1, y2)), with
+    /// the challenge derived from the transcript instead of being sent by the Verifier
+    pub fn prove(&self, x: &G::Scalar, transcript: Transcript) -> Proof<G> {
+        let (y1, y2) = self.gen_public(x);
+
+        let k = match self.nonce_generation {
+            NonceGeneration::Random => self.gen_nonce(),
+            NonceGeneration::Derived => self.derive_nonce(x, &transcript.clone().digest()),
+        };
+        let (r1, r2) = self.gen_random(&k);
+
+        let c = transcript.challenge(&self.group, (&y1, &y2), (&r1, &r2));
+        let s = self.answer(&c, k.k.expose(), x);
+
+        Proof { r1, r2, s }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{extract, Conversation};
+    use crate::{ModPGroup, Ristretto255Group, Verifier};
+
+    fn nonce_reuse<G: Group>(group: G) {
+        let prover = Prover::new(group.clone());
+        let verifier = Verifier::new(group.clone());
+
+        let x = group.random_scalar();
+        let (y1, y2) = prover.gen_public(&x);
+
+        let k = prover.gen_nonce();
+        let (r1, r2) = prover.gen_random(&k);
+        let (c1, c2) = (verifier.request_challenge(), verifier.request_challenge());
+
+        // the nonce is never printed
+        assert!(!format!("{k:?}").contains(&format!("{:?}", k.k.expose())));
+
+        let s1 = prover.challenge_answer(&c1, &k, &x).unwrap();
+        assert!(verifier.verify(&s1, &c1, &y1, &y2, &r1, &r2));
+
+        // answering the same challenge again gives nothing away
+        assert_eq!(prover.challenge_answer(&c1, &k, &x), Ok(s1.clone()));
+        assert_eq!(prover.challenge_answer(&c2, &k, &x), Err(Error::NonceReuse));
+
+        // a Prover without the guard answers the second challenge too, and leaks x
+        let s2 = prover.answer(&c2, k.k.expose(), &x);
+        assert!(verifier.verify(&s2, &c2, &y1, &y2, &r1, &r2));
+        let conversation = |c, s| Conversation::<G> {
+            r1: r1.clone(),
+            r2: r2.clone(),
+            c,
+            s,
+        };
+        assert_eq!(
+            extract(&group, &conversation(c1, s1), &conversation(c2, s2)),
+            Some(x)
+        );
+    }
+
+    #[test]
+    fn test_nonce_reuse() {
+        nonce_reuse(ModPGroup::default());
+    }
+
+    #[test]
+    fn test_nonce_reuse_ristretto255() {
+        nonce_reuse(Ristretto255Group::default());
+    }
+
+    fn derived_nonces<G: Group>(group: G) {
+        let prover = Prover::new(group.clone()).with_nonce_generation(NonceGeneration::Derived);
+        let verifier = Verifier::new(group.clone());
+
+        let x = group.random_scalar();
+        let (y1, y2) = prover.gen_public(&x);
+
+        let transcript = || Transcript::login("zkp_server", "Bob", 1000);
+        let proof = prover.prove(&x, transcript());
+        assert!(verifier.verify_proof(&y1, &y2, &proof, transcript()));
+
+        // with a broken RNG, the nonce is still bound to the secret and the context
+        let context = transcript().digest();
+        let nonce =
+            |x: &G::Scalar, context: &[u8]| prover.derive_nonce_with(x, context, &[0; 32]).k;
+
+        let k = nonce(&x, &context);
+        assert_eq!(nonce(&x, &context), k);
+        assert_ne!(
+            nonce(&x, &Transcript::login("zkp_server", "Bob", 1001).digest()),
+            k
+        );
+        assert_ne!(nonce(&group.random_scalar(), &context), k);
+
+        // and fresh randomness makes it differ for the same secret and context
+        assert_ne!(
+            prover.derive_nonce(&x, &context).k,
+            prover.derive_nonce(&x, &context).k
+        );
+    }
+
+    #[test]
+    fn test_derived_nonces() {
+        derived_nonces(ModPGroup::default());
+    }
+
+    #[test]
+    fn test_derived_nonces_ristretto255() {
+        derived_nonces(Ristretto255Group::default());
+    }
+}
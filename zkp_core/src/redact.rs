@@ -0,0 +1,96 @@
+//! Redaction of secrets from the `tracing` output of the client and the server.
+//!
+//! Secret values are held in [`Secret`](crate::Secret)s, whose `Debug` is always `[REDACTED]`.
+//! On top of that, the fields of the events and spans that are named after a secret (e.g.,
+//! `debug!(password = ?password)`) are printed as `[REDACTED]` by the formatter installed by
+//! [`init`], whatever the log level - so a secret that is not wrapped does not leak either.
+
+use tracing::field::Field;
+use tracing_subscriber::field::MakeExt;
+use tracing_subscriber::fmt::format::{self, Writer};
+use tracing_subscriber::fmt::FormatFields;
+
+/// What the secrets are printed as
+pub const REDACTED: &str = "[REDACTED]";
+
+/// The names of the fields which hold secrets
+pub const SECRET_FIELDS: &[&str] = &["password", "secret", "x", "k", "nonce"];
+
+/// Format the fields of events and spans, with the secret ones redacted
+pub fn fields() -> impl for<'writer> FormatFields<'writer> + Send + Sync + 'static {
+    format::debug_fn(
+        |writer: &mut Writer<'_>, field: &Field, value: &dyn std::fmt::Debug| match field.name() {
+            "message" => write!(writer, "{value:?}"),
+            name if SECRET_FIELDS.contains(&name) => write!(writer, "{name}={REDACTED}"),
+            name => write!(writer, "{name}={value:?}"),
+        },
+    )
+    .delimited(" ")
+}
+
+/// Install the global `tracing` subscriber (configured as `tracing_subscriber::fmt::init` is),
+/// with the secret fields redacted
+pub fn init() {
+    tracing_subscriber::fmt().fmt_fields(fields()).init();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{debug, info};
+
+    use super::*;
+    use crate::Secret;
+
+    /// A writer which appends to a shared buffer
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Capture {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_redaction() {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let writer = output.clone();
+
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_ansi(false)
+            .fmt_fields(fields())
+            .with_writer(move || Capture(writer.clone()))
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let password = Secret::new(String::from("hunter2"));
+
+            info!(user = "Bob", password = "hunter2", "registering");
+            debug!(x = 987374283748273489u64, k = ?4242424242424242u64, "answering");
+            debug!("payload: {password:?}");
+        });
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+
+        assert!(
+            output.contains(r#"registering user="Bob" password=[REDACTED]"#),
+            "{output}"
+        );
+        assert!(
+            output.contains("answering x=[REDACTED] k=[REDACTED]"),
+            "{output}"
+        );
+        assert!(output.contains("payload: [REDACTED]"), "{output}");
+
+        for secret in ["hunter2", "987374283748273489", "4242424242424242"] {
+            assert!(!output.contains(secret), "{output}");
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Derivation of the nonce `k` with HMAC-DRBG (HMAC-SHA256), in the style of RFC 6979.
+//!
+//! `k` is derived from the secret `x`, a digest of the context of the proof, and (optionally)
+//! fresh randomness - the "additional data" of section 3.6 of the RFC. With fresh randomness
+//! the nonce is "hedged": it stays unpredictable if the system RNG is weak or repeats itself
+//! (e.g., after a fork), as long as `x` is secret, and it stays random if `x` is ever derived
+//! from a weak password. Without it, the derivation is exactly the one of RFC 6979, which is
+//! what the test vectors check.
+
+use hmac::{Hmac, Mac};
+use num_bigint::BigUint;
+use num_traits::Zero;
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::Secret;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The HMAC_DRBG state of section 3.2 of RFC 6979 - the key `K` and the value `V` (wiped when
+/// dropped, since `k` is derived from them)
+struct HmacDrbg {
+    k: [u8; 32],
+    v: [u8; 32],
+}
+
+impl HmacDrbg {
+    /// Steps b to g - seed the generator with the concatenation of `seed`
+    fn new(seed: &[&[u8]]) -> Self {
+        let mut drbg = HmacDrbg {
+            k: [0x00; 32],
+            v: [0x01; 32],
+        };
+
+        for round in [[0x00], [0x01]] {
+            let mut data: Vec<&[u8]> = vec![&drbg.v, &round];
+            data.extend_from_slice(seed);
+
+            drbg.k = drbg.mac(&data);
+            drbg.v = drbg.mac(&[&drbg.v]);
+        }
+
+        drbg
+    }
+
+    /// HMAC_K(data[0] || data[1] || ...)
+    fn mac(&self, data: &[&[u8]]) -> [u8; 32] {
+        let mut mac = HmacSha256::new_from_slice(&self.k).expect("HMAC accepts keys of any size");
+        for part in data {
+            mac.update(part);
+        }
+        mac.finalize().into_bytes().into()
+    }
+
+    /// Step h.2 - at least `len` bytes of output
+    fn generate(&mut self, len: usize) -> Vec<u8> {
+        let mut output = Vec::with_capacity(len + 32);
+        while output.len() < len {
+            self.v = self.mac(&[&self.v]);
+            output.extend_from_slice(&self.v);
+        }
+        output
+    }
+
+    /// Step h.3 - update the state after a candidate has been rejected
+    fn reject(&mut self) {
+        self.k = self.mac(&[&self.v, &[0x00]]);
+        self.v = self.mac(&[&self.v]);
+    }
+}
+
+/// The leftmost `qlen` bits of the bytes, as an integer (section 2.3.2)
+fn bits2int(bytes: &[u8], qlen: u64) -> BigUint {
+    let int = BigUint::from_bytes_be(bytes);
+    let blen = 8 * bytes.len() as u64;
+
+    if blen > qlen {
+        int >> (blen - qlen)
+    } else {
+        int
+    }
+}
+
+/// The integer, as `rlen / 8` big-endian bytes (section 2.3.3)
+fn int2octets(int: &BigUint, rlen: usize) -> Vec<u8> {
+    let bytes = int.to_bytes_be();
+    let mut octets = vec![0u8; rlen.saturating_sub(bytes.len())];
+    octets.extend_from_slice(&bytes[bytes.len().saturating_sub(rlen)..]);
+    octets
+}
+
+/// The bytes, as an integer reduced modulo q, as `rlen / 8` bytes (section 2.3.4)
+fn bits2octets(bytes: &[u8], q: &BigUint, rlen: usize) -> Vec<u8> {
+    let z1 = bits2int(bytes, q.bits());
+    let z2 = if z1 >= *q { z1 - q } else { z1 };
+    int2octets(&z2, rlen)
+}
+
+/// The nonce `k`, in [1, q), for the secret `x` (in [1, q)) and the digest `h1` of the message
+/// (or the context of the proof), with the additional data mixed into the seed of the generator
+pub fn generate_k(q: &BigUint, x: &BigUint, h1: &[u8], additional_data: &[u8]) -> BigUint {
+    let qlen = q.bits();
+    let rlen = qlen.div_ceil(8) as usize;
+
+    let x = Secret::new(int2octets(x, rlen));
+    let mut drbg = HmacDrbg::new(&[x.expose(), &bits2octets(h1, q, rlen), additional_data]);
+
+    loop {
+        let k = bits2int(Secret::new(drbg.generate(rlen)).expose(), qlen);
+
+        if !k.is_zero() && k < *q {
+            return k;
+        }
+
+        drbg.reject();
+    }
+}
+
+impl Drop for HmacDrbg {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.v.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::Digest;
+
+    use super::*;
+
+    fn hex(encoded: &str) -> BigUint {
+        BigUint::parse_bytes(encoded.as_bytes(), 16).unwrap()
+    }
+
+    /// The test vectors of RFC 6979 with SHA-256 - the 163-bit q of the detailed example
+    /// (appendix A.1) and the NIST P-256 curve (appendix A.2.5)
+    #[test]
+    fn test_rfc6979_vectors() {
+        let vectors = [
+            (
+                "4000000000000000000020108A2E0CC0D99F8A5EF",
+                "09A4D6792295A7F730FC3F2B49CBC0F62E862272F",
+                "sample",
+                "23AF4074C90A02B3FE61D286D5C87F425E6BDD81B",
+            ),
+            (
+                "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+                "C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721",
+                "sample",
+                "A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60",
+            ),
+            (
+                "FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551",
+                "C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721",
+                "test",
+                "D16B6AE827F17175E040871A1C7EC3500192C4C92677336EC2537ACAEE0008E0",
+            ),
+        ];
+
+        for (q, x, message, k) in vectors {
+            let h1 = Sha256::digest(message.as_bytes());
+            assert_eq!(generate_k(&hex(q), &hex(x), &h1, &[]), hex(k), "{message}");
+        }
+    }
+
+    #[test]
+    fn test_additional_data() {
+        let (q, x) = (
+            hex("FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551"),
+            hex("C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721"),
+        );
+        let h1 = Sha256::digest(b"sample");
+
+        let k = generate_k(&q, &x, &h1, &[]);
+        assert_ne!(generate_k(&q, &x, &h1, &[0; 32]), k);
+        assert_ne!(
+            generate_k(&q, &x, &h1, &[0; 32]),
+            generate_k(&q, &x, &h1, &[1; 32])
+        );
+
+        // a tiny q rejects most of the candidates
+        let q = BigUint::from(11u32);
+        for additional_data in 0..64u8 {
+            let k = generate_k(&q, &BigUint::from(3u32), &h1, &[additional_data]);
+            assert!(!k.is_zero() && k < q);
+        }
+    }
+}
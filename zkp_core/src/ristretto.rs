@@ -0,0 +1,269 @@
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, IsIdentity, VartimeMultiscalarMul};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use sha2::Sha512;
+use subtle::ConstantTimeEq;
+
+use crate::{Error, Group};
+
+/// The seed from which the second generator, h, is derived
+const H_SEED: &[u8] = b"zkp_chaum_pedersen ristretto255 h";
+
+/// The Ristretto255 prime-order group (built on top of Curve25519), with the standard basepoint as
+/// `g`, and `h` derived by hashing a fixed seed onto the group (so that log_g(h) is not known).
+///
+/// Elements are exchanged as hex-encoded compressed points (32 bytes), and scalars as hex-encoded
+/// canonical little-endian scalars (32 bytes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ristretto255Group {
+    g: RistrettoPoint,
+    h: RistrettoPoint,
+}
+
+impl Default for Ristretto255Group {
+    fn default() -> Self {
+        Ristretto255Group {
+            g: RISTRETTO_BASEPOINT_POINT,
+            h: RistrettoPoint::hash_from_bytes::<Sha512>(H_SEED),
+        }
+    }
+}
+
+fn decode_32_bytes(encoded: &str, what: &str) -> Result<[u8; 32], Error> {
+    hex::decode(encoded)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| Error::InvalidEncoding(format!("{what} is not 32 hex-encoded bytes")))
+}
+
+impl Group for Ristretto255Group {
+    type Element = RistrettoPoint;
+    type Scalar = Scalar;
+
+    fn description(&self) -> String {
+        "ristretto255".into()
+    }
+
+    /// The group is of prime order by construction, so only the generators need checking - g
+    /// must be the basepoint, and h must be derived from its seed (and neither is the identity)
+    fn check(&self) -> Result<(), Error> {
+        let invalid = |reason: &str| Err(Error::InvalidParameters(reason.to_string()));
+
+        if self.g != RISTRETTO_BASEPOINT_POINT {
+            return invalid("g is not the Ristretto255 basepoint");
+        }
+
+        if self.h != RistrettoPoint::hash_from_bytes::<Sha512>(H_SEED) {
+            return invalid("h was not derived from its seed");
+        }
+
+        if self.g.is_identity() || self.h.is_identity() {
+            return invalid("g or h is the identity");
+        }
+
+        Ok(())
+    }
+
+    fn g(&self) -> &RistrettoPoint {
+        &self.g
+    }
+
+    fn h(&self) -> &RistrettoPoint {
+        &self.h
+    }
+
+    fn identity(&self) -> RistrettoPoint {
+        RistrettoPoint::identity()
+    }
+
+    /// Every decoded point is in the group of prime order (Ristretto has no cofactor), so only
+    /// the identity needs to be rejected
+    fn validate_element(&self, element: &RistrettoPoint) -> Result<(), Error> {
+        if element.is_identity() {
+            return Err(Error::InvalidElement("the identity".into()));
+        }
+
+        Ok(())
+    }
+
+    fn exp(&self, base: &RistrettoPoint, exponent: &Scalar) -> RistrettoPoint {
+        base * exponent
+    }
+
+    /// The scalar multiplication of dalek is constant-time
+    fn exp_secret(&self, base: &RistrettoPoint, exponent: &Scalar) -> RistrettoPoint {
+        base * exponent
+    }
+
+    /// Variable time, since it is only used for verification (of public values)
+    fn multi_exp(&self, terms: &[(&RistrettoPoint, &Scalar)]) -> RistrettoPoint {
+        RistrettoPoint::vartime_multiscalar_mul(
+            terms.iter().map(|(_, scalar)| *scalar),
+            terms.iter().map(|(point, _)| *point),
+        )
+    }
+
+    fn mul(&self, a: &RistrettoPoint, b: &RistrettoPoint) -> RistrettoPoint {
+        a + b
+    }
+
+    fn ct_eq(&self, a: &RistrettoPoint, b: &RistrettoPoint) -> bool {
+        a.ct_eq(b).into()
+    }
+
+    /// The prime l = 2^252 + 27742317777372353535851937790883648493
+    fn order(&self) -> BigUint {
+        BigUint::from_bytes_le((-Scalar::ONE).as_bytes()) + 1u32
+    }
+
+    fn random_scalar(&self) -> Scalar {
+        Scalar::random(&mut OsRng)
+    }
+
+    /// Scalars are always reduced, since only canonical encodings are decoded
+    fn scalar_is_reduced(&self, _scalar: &Scalar) -> bool {
+        true
+    }
+
+    fn scalar_add(&self, a: &Scalar, b: &Scalar) -> Scalar {
+        a + b
+    }
+
+    fn scalar_mul(&self, a: &Scalar, b: &Scalar) -> Scalar {
+        a * b
+    }
+
+    fn scalar_sub(&self, a: &Scalar, b: &Scalar) -> Scalar {
+        a - b
+    }
+
+    /// The scalar arithmetic of dalek is constant-time
+    fn scalar_mul_sub(&self, a: &Scalar, b: &Scalar, c: &Scalar) -> Scalar {
+        a - b * c
+    }
+
+    fn scalar_neg(&self, a: &Scalar) -> Scalar {
+        -a
+    }
+
+    fn scalar_invert(&self, a: &Scalar) -> Option<Scalar> {
+        (*a != Scalar::ZERO).then(|| a.invert())
+    }
+
+    /// The bytes are read as a big-endian integer, and reduced modulo the group order
+    fn scalar_from_bytes(&self, bytes: &[u8]) -> Scalar {
+        let radix = Scalar::from(256u64);
+        bytes
+            .iter()
+            .fold(Scalar::ZERO, |acc, &byte| acc * radix + Scalar::from(byte))
+    }
+
+    fn scalar_to_bytes(&self, scalar: &Scalar) -> Vec<u8> {
+        scalar.as_bytes().iter().rev().copied().collect()
+    }
+
+    fn encode_element(&self, element: &RistrettoPoint) -> String {
+        hex::encode(element.compress().as_bytes())
+    }
+
+    fn decode_element(&self, encoded: &str) -> Result<RistrettoPoint, Error> {
+        CompressedRistretto(decode_32_bytes(encoded, "group element")?)
+            .decompress()
+            .ok_or_else(|| Error::InvalidEncoding("group element is not a valid point".into()))
+    }
+
+    fn encode_scalar(&self, scalar: &Scalar) -> String {
+        hex::encode(scalar.as_bytes())
+    }
+
+    fn decode_scalar(&self, encoded: &str) -> Result<Scalar, Error> {
+        Option::from(Scalar::from_canonical_bytes(decode_32_bytes(
+            encoded, "scalar",
+        )?))
+        .ok_or_else(|| Error::InvalidEncoding("scalar is not canonical".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Prover, Verifier};
+
+    #[test]
+    fn test_verify() {
+        let prover = Prover::new(Ristretto255Group::default());
+        let verifier = Verifier::new(Ristretto255Group::default());
+
+        let x = prover.group().scalar_from_bytes(b"987374283748273489");
+        let (y1, y2) = prover.gen_public(&x);
+
+        let k = prover.gen_nonce();
+        let (r1, r2) = prover.gen_random(&k);
+        let c = verifier.request_challenge();
+
+        let s = prover.challenge_answer(&c, &k, &x).unwrap();
+        assert!(verifier.verify(&s, &c, &y1, &y2, &r1, &r2));
+
+        let s = prover.challenge_answer(&c, &k, &(x + Scalar::ONE)).unwrap();
+        assert!(!verifier.verify(&s, &c, &y1, &y2, &r1, &r2));
+    }
+
+    #[test]
+    fn test_encoding() {
+        let group = Ristretto255Group::default();
+        let element = group.exp(group.h(), &group.random_scalar());
+        let scalar = group.random_scalar();
+
+        assert_eq!(
+            group.decode_element(&group.encode_element(&element)),
+            Ok(element)
+        );
+        assert_eq!(
+            group.decode_scalar(&group.encode_scalar(&scalar)),
+            Ok(scalar)
+        );
+        assert!(group.decode_element("00ff").is_err());
+        assert!(group.decode_scalar(&"ff".repeat(32)).is_err());
+    }
+
+    #[test]
+    fn test_validate_element() {
+        let group = Ristretto255Group::default();
+
+        assert_eq!(group.validate_element(group.h()), Ok(()));
+        assert!(group
+            .validate_element(&group.decode_element(&"00".repeat(32)).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        assert_eq!(Ristretto255Group::default().check(), Ok(()));
+
+        let swapped = Ristretto255Group {
+            g: Ristretto255Group::default().h,
+            h: RISTRETTO_BASEPOINT_POINT,
+        };
+        assert!(swapped.check().is_err());
+    }
+
+    #[test]
+    fn test_scalar_from_bytes() {
+        let group = Ristretto255Group::default();
+
+        assert_eq!(group.scalar_from_bytes(&[1, 2]), Scalar::from(258u64));
+
+        let scalar = group.random_scalar();
+        assert_eq!(
+            group.scalar_from_bytes(&group.scalar_to_bytes(&scalar)),
+            scalar
+        );
+        assert_eq!(
+            group.order(),
+            (BigUint::from(1u32) << 252) + 27742317777372353535851937790883648493u128
+        );
+    }
+}
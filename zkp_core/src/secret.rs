@@ -0,0 +1,114 @@
+//! Secret values - the secret `x`, the nonce `k` and the password of the user - which are wiped
+//! from memory when they are dropped, and always printed as `[REDACTED]` by `Debug`.
+//!
+//! Wiping is best effort: the arithmetic on big integers allocates temporaries which are not
+//! wiped, but no long-lived copy of a secret is left behind by the Prover.
+
+use std::fmt;
+use std::sync::atomic::{compiler_fence, Ordering};
+
+use curve25519_dalek::scalar::Scalar;
+use num_bigint::BigUint;
+use serde::Deserialize;
+use zeroize::Zeroize;
+
+use crate::redact::REDACTED;
+
+/// A value which can be overwritten in place
+pub trait Wipe {
+    fn wipe(&mut self);
+}
+
+impl Wipe for BigUint {
+    /// Overwrite the digits with zeros - the buffer is large enough for them, so it is written in
+    /// place rather than reallocated
+    fn wipe(&mut self) {
+        let zeros = vec![0u32; self.bits().div_ceil(32) as usize];
+        self.assign_from_slice(&zeros);
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+impl Wipe for Scalar {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl Wipe for String {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl Wipe for Vec<u8> {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// A secret value, which is wiped when dropped and redacted from `Debug`
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret<T: Wipe>(T);
+
+impl<T: Wipe> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    /// The secret value - it must not be copied into a value that is not itself a [`Secret`]
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Wipe> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T: Wipe> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.wipe();
+    }
+}
+
+impl<T: Wipe> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::Zero;
+
+    use super::*;
+
+    #[test]
+    fn test_wipe() {
+        let mut x = BigUint::from(987374283748273489u64) << 1000u32;
+        x.wipe();
+        assert!(x.is_zero());
+
+        let mut password = String::from("correct horse battery staple");
+        password.wipe();
+        assert!(password.is_empty());
+
+        let mut k = Scalar::from(42u64);
+        k.wipe();
+        assert_eq!(k, Scalar::ZERO);
+    }
+
+    #[test]
+    fn test_debug() {
+        let password = Secret::new(String::from("correct horse battery staple"));
+        assert_eq!(format!("{password:?}"), "[REDACTED]");
+
+        let password: Secret<String> = serde_json::from_str(r#""hunter2""#).unwrap();
+        assert_eq!(password.expose(), "hunter2");
+        assert!(!format!("{:?}", Some(&password)).contains("hunter2"));
+    }
+}
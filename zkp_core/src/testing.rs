@@ -0,0 +1,276 @@
+//! Test support for the protocol itself - an honest-verifier simulator and a special-soundness
+//! extractor, the two halves of the proof that Chaum-Pedersen is a sigma protocol:
+//!
+//!   * Zero-knowledge: [`simulate`] produces accepting conversations (r1, r2, c, s) for any public
+//!     data (y1, y2), without `x`, with the same distribution as the honest ones for a given
+//!     challenge. So a conversation with an honest Verifier teaches it nothing it could not have
+//!     produced alone.
+//!   * Soundness: [`extract`] recovers `x` from two accepting conversations with the same
+//!     commitment and different challenges. So a Prover which can answer more than one challenge
+//!     for a commitment knows `x`.
+//!
+//! Available in the tests of `zkp_core`, and to other crates with the `testing` feature.
+
+use std::sync::OnceLock;
+
+use crate::{Group, ModPGroup, ParameterSet, Verifier};
+
+/// The groups of every supported parameter set (see [`ParameterSet::ALL`]), built once
+pub fn groups() -> &'static [ModPGroup] {
+    static GROUPS: OnceLock<Vec<ModPGroup>> = OnceLock::new();
+    GROUPS.get_or_init(|| ParameterSet::ALL.iter().map(ParameterSet::group).collect())
+}
+
+/// A run of the (interactive) protocol, as seen by the Verifier - the commitment (r1, r2), the
+/// challenge `c` and the answer `s`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversation<G: Group> {
+    pub r1: G::Element,
+    pub r2: G::Element,
+    pub c: G::Scalar,
+    pub s: G::Scalar,
+}
+
+impl<G: Group> Conversation<G> {
+    /// Whether the Verifier accepts the conversation for the public data (y1, y2)
+    pub fn accepted_by(&self, verifier: &Verifier<G>, y1: &G::Element, y2: &G::Element) -> bool {
+        verifier.verify(&self.s, &self.c, y1, y2, &self.r1, &self.r2)
+    }
+}
+
+/// The conversation of an honest Prover with the secret `x` and the nonce `k`, for the challenge
+/// `c` - unlike [`Prover`](crate::Prover), nothing stops `k` from answering several challenges
+/// (i.e., the Prover can be rewound)
+pub fn honest<G: Group>(group: &G, x: &G::Scalar, k: &G::Scalar, c: &G::Scalar) -> Conversation<G> {
+    Conversation {
+        r1: group.exp(group.g(), k),
+        r2: group.exp(group.h(), k),
+        c: c.clone(),
+        s: group.scalar_mul_sub(k, c, x),
+    }
+}
+
+/// An accepting conversation for the public data (y1, y2) and the challenge `c`, without the
+/// secret - the answer `s` is drawn first, and the commitment is solved for:
+/// r1 = g^s . y1^c and r2 = h^s . y2^c
+pub fn simulate<G: Group>(
+    group: &G,
+    (y1, y2): (&G::Element, &G::Element),
+    c: &G::Scalar,
+) -> Conversation<G> {
+    simulate_with(group, (y1, y2), c, group.random_scalar())
+}
+
+/// [`simulate`], with the given answer
+fn simulate_with<G: Group>(
+    group: &G,
+    (y1, y2): (&G::Element, &G::Element),
+    c: &G::Scalar,
+    s: G::Scalar,
+) -> Conversation<G> {
+    Conversation {
+        r1: group.multi_exp(&[(group.g(), &s), (y1, c)]),
+        r2: group.multi_exp(&[(group.h(), &s), (y2, c)]),
+        c: c.clone(),
+        s,
+    }
+}
+
+/// Recover the secret from two conversations with the same commitment and different challenges:
+/// s1 - s2 = (c2 - c1) . x, so x = (s1 - s2) / (c2 - c1).
+///
+/// `None` if the commitments differ, or the challenges are the same. The result is only the
+/// secret if both conversations are accepting.
+pub fn extract<G: Group>(
+    group: &G,
+    first: &Conversation<G>,
+    second: &Conversation<G>,
+) -> Option<G::Scalar> {
+    if first.r1 != second.r1 || first.r2 != second.r2 {
+        return None;
+    }
+
+    let inverse = group.scalar_invert(&group.scalar_sub(&second.c, &first.c))?;
+    Some(group.scalar_mul(&group.scalar_sub(&first.s, &second.s), &inverse))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use num_bigint::BigUint;
+    use num_traits::ToPrimitive;
+
+    use super::*;
+    use crate::{Prover, Ristretto255Group};
+
+    /// The subgroup of order 11 of Z_23^*, small enough to go through every scalar
+    fn tiny() -> ModPGroup {
+        let n = BigUint::from;
+        ModPGroup::new(n(23u32), n(11u32), n(4u32), n(9u32))
+    }
+
+    /// Every scalar of the group, [0, q)
+    fn scalars(group: &ModPGroup) -> impl Iterator<Item = BigUint> {
+        (0..group.q().to_u32().unwrap()).map(BigUint::from)
+    }
+
+    fn completeness<G: Group>(group: G) {
+        let prover = Prover::new(group.clone());
+        let verifier = Verifier::new(group.clone());
+
+        for _ in 0..4 {
+            let x = group.random_scalar();
+            let (y1, y2) = prover.gen_public(&x);
+
+            // through the Prover
+            let k = prover.gen_nonce();
+            let (r1, r2) = prover.gen_random(&k);
+            let c = verifier.request_challenge();
+            let s = prover.challenge_answer(&c, &k, &x).unwrap();
+            assert!(verifier.verify(&s, &c, &y1, &y2, &r1, &r2));
+
+            // and through the rewindable one
+            let conversation = honest(&group, &x, &group.random_scalar(), &c);
+            assert!(conversation.accepted_by(&verifier, &y1, &y2));
+        }
+    }
+
+    #[test]
+    fn test_completeness() {
+        completeness(ModPGroup::default());
+        completeness(tiny());
+    }
+
+    #[test]
+    fn test_completeness_ristretto255() {
+        completeness(Ristretto255Group::default());
+    }
+
+    /// A random scalar other than zero - with c = 0, the answer does not depend on the secret
+    /// (which happens often enough in the tiny group)
+    fn nonzero_scalar<G: Group>(group: &G) -> G::Scalar {
+        let zero = group.scalar_from_bytes(&[0]);
+
+        loop {
+            let scalar = group.random_scalar();
+            if scalar != zero {
+                return scalar;
+            }
+        }
+    }
+
+    fn soundness<G: Group>(group: G) {
+        let verifier = Verifier::new(group.clone());
+
+        let one = group.scalar_from_bytes(&[1]);
+        let x = group.random_scalar();
+        let (y1, y2) = Prover::new(group.clone()).gen_public(&x);
+
+        // a Prover which answers two challenges for the same commitment gives the secret away
+        let k = group.random_scalar();
+        let c1 = nonzero_scalar(&group);
+        let c2 = group.scalar_add(&c1, &one);
+        let (first, second) = (honest(&group, &x, &k, &c1), honest(&group, &x, &k, &c2));
+        assert!(first.accepted_by(&verifier, &y1, &y2));
+        assert!(second.accepted_by(&verifier, &y1, &y2));
+        assert_eq!(extract(&group, &first, &second), Some(x.clone()));
+
+        // but only from the same commitment, and different challenges
+        let other = honest(&group, &x, &group.scalar_add(&k, &one), &c2);
+        assert_eq!(extract(&group, &first, &other), None);
+        assert_eq!(extract(&group, &first, &first), None);
+
+        // a wrong secret does not verify (unless c = 0, which is why c1 is not)
+        let wrong = honest(&group, &group.scalar_add(&x, &one), &k, &c1);
+        assert!(!wrong.accepted_by(&verifier, &y1, &y2));
+    }
+
+    #[test]
+    fn test_soundness() {
+        soundness(ModPGroup::default());
+        soundness(tiny());
+    }
+
+    #[test]
+    fn test_soundness_ristretto255() {
+        soundness(Ristretto255Group::default());
+    }
+
+    /// For public data with log_g(y1) != log_h(y2) - for which there is no secret - every
+    /// commitment is accepted for at most one challenge, so a cheating Prover is caught with
+    /// probability 1 - 1/q
+    #[test]
+    fn test_soundness_exhaustive() {
+        let group = tiny();
+        let verifier = Verifier::new(group.clone());
+        let (y1, y2) = (
+            group.exp(group.g(), &3u32.into()),
+            group.exp(group.h(), &5u32.into()),
+        );
+
+        for (k1, k2) in
+            scalars(&group).flat_map(|k1| scalars(&group).map(move |k2| (k1.clone(), k2)))
+        {
+            let (r1, r2) = (group.exp(group.g(), &k1), group.exp(group.h(), &k2));
+
+            let challenges = scalars(&group)
+                .filter(|c| scalars(&group).any(|s| verifier.verify(&s, c, &y1, &y2, &r1, &r2)))
+                .count();
+            assert!(challenges <= 1, "{challenges} challenges for ({r1}, {r2})");
+        }
+    }
+
+    fn zero_knowledge<G: Group>(group: G) {
+        let verifier = Verifier::new(group.clone());
+
+        // any public data, whether or not it has a secret
+        let (y1, y2) = (
+            group.exp(group.g(), &group.random_scalar()),
+            group.exp(group.h(), &group.random_scalar()),
+        );
+
+        for _ in 0..4 {
+            let c = verifier.request_challenge();
+            let conversation = simulate(&group, (&y1, &y2), &c);
+            assert!(conversation.accepted_by(&verifier, &y1, &y2));
+            assert_eq!(conversation.c, c);
+        }
+    }
+
+    #[test]
+    fn test_zero_knowledge() {
+        zero_knowledge(ModPGroup::default());
+        zero_knowledge(tiny());
+    }
+
+    #[test]
+    fn test_zero_knowledge_ristretto255() {
+        zero_knowledge(Ristretto255Group::default());
+    }
+
+    /// For every challenge, the honest conversations over all the nonces are exactly the
+    /// simulated ones over all the answers - so both are uniform over the same set
+    #[test]
+    fn test_zero_knowledge_distribution() {
+        let group = tiny();
+        let x = BigUint::from(7u32);
+        let (y1, y2) = Prover::new(group.clone()).gen_public(&x);
+
+        let key = |conversation: Conversation<ModPGroup>| {
+            (conversation.r1, conversation.r2, conversation.s)
+        };
+
+        for c in scalars(&group) {
+            let real = scalars(&group)
+                .map(|k| key(honest(&group, &x, &k, &c)))
+                .collect::<HashSet<_>>();
+            let simulated = scalars(&group)
+                .map(|s| key(simulate_with(&group, (&y1, &y2), &c, s)))
+                .collect::<HashSet<_>>();
+
+            assert_eq!(real.len(), 11);
+            assert_eq!(real, simulated);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Domain-separated transcripts for the non-interactive (Fiat-Shamir) flavor of the protocol,
+//! where the challenge `c` is derived by hashing everything the Verifier would have seen, rather
+//! than being sent by the Verifier.
+
+use sha2::{Digest, Sha512};
+
+use crate::Group;
+
+/// The environment variable from which the identity of the server is read - the client and the
+/// server must agree on it, since it is bound into the login transcript
+pub const SERVER_ID_ENV_VAR: &str = "ZKP_SERVER_ID";
+
+/// The identity of the server, if none is configured
+pub const DEFAULT_SERVER_ID: &str = "zkp_server";
+
+/// The domain separator for login proofs
+const LOGIN_DOMAIN: &[u8] = b"zkp_chaum_pedersen/login/v1";
+
+/// The domain separator for registration proofs
+const REGISTRATION_DOMAIN: &[u8] = b"zkp_chaum_pedersen/registration/v1";
+
+/// Read the identity of the server from the environment
+pub fn server_id_from_env() -> String {
+    std::env::var(SERVER_ID_ENV_VAR).unwrap_or_else(|_| DEFAULT_SERVER_ID.to_string())
+}
+
+/// A transcript of labelled values, hashed with SHA-512. Every value is prefixed with the lengths
+/// of its label and of itself, so that no two different transcripts hash the same input.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    hasher: Sha512,
+}
+
+impl Transcript {
+    pub fn new(domain: &[u8]) -> Self {
+        let mut transcript = Transcript {
+            hasher: Sha512::new(),
+        };
+        transcript.append(b"domain", domain);
+        transcript
+    }
+
+    /// The transcript for a login of the user with the server, at the given time (in seconds
+    /// since the Unix epoch)
+    pub fn login(server_id: &str, user: &str, timestamp: u64) -> Self {
+        let mut transcript = Transcript::new(LOGIN_DOMAIN);
+        transcript.append(b"server_id", server_id.as_bytes());
+        transcript.append(b"user", user.as_bytes());
+        transcript.append(b"timestamp", &timestamp.to_be_bytes());
+        transcript
+    }
+
+    /// The transcript for the registration of the user with the server, with the salt of its KDF
+    /// (a registration proof cannot be replayed as a login proof, or for another registration)
+    pub fn registration(server_id: &str, user: &str, salt: &[u8]) -> Self {
+        let mut transcript = Transcript::new(REGISTRATION_DOMAIN);
+        transcript.append(b"server_id", server_id.as_bytes());
+        transcript.append(b"user", user.as_bytes());
+        transcript.append(b"salt", salt);
+        transcript
+    }
+
+    pub fn append(&mut self, label: &[u8], value: &[u8]) {
+        self.hasher.update((label.len() as u64).to_be_bytes());
+        self.hasher.update(label);
+        self.hasher.update((value.len() as u64).to_be_bytes());
+        self.hasher.update(value);
+    }
+
+    /// The digest of the transcript so far
+    pub fn digest(self) -> Vec<u8> {
+        self.hasher.finalize().to_vec()
+    }
+
+    pub fn append_element<G: Group>(&mut self, group: &G, label: &[u8], element: &G::Element) {
+        self.append(label, group.encode_element(element).as_bytes());
+    }
+
+    /// The challenge for a proof of (y1, y2) with the commitment (r1, r2) - the parameters of the
+    /// group are bound into the challenge as well
+    pub fn challenge<G: Group>(
+        mut self,
+        group: &G,
+        (y1, y2): (&G::Element, &G::Element),
+        (r1, r2): (&G::Element, &G::Element),
+    ) -> G::Scalar {
+        self.append(b"group", group.description().as_bytes());
+        self.append_element(group, b"g", group.g());
+        self.append_element(group, b"h", group.h());
+        self.append_element(group, b"y1", y1);
+        self.append_element(group, b"y2", y2);
+        self.append_element(group, b"r1", r1);
+        self.append_element(group, b"r2", r2);
+
+        group.scalar_from_bytes(&self.digest())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModPGroup;
+
+    #[test]
+    fn test_challenge() {
+        let group = ModPGroup::default();
+        let (g, h) = (group.g(), group.h());
+
+        let challenge = |transcript: Transcript| transcript.challenge(&group, (g, h), (h, g));
+
+        let c = challenge(Transcript::login("zkp_server", "Bob", 1));
+        assert_eq!(c, challenge(Transcript::login("zkp_server", "Bob", 1)));
+
+        assert_ne!(c, challenge(Transcript::login("zkp_server", "Bob", 2)));
+        assert_ne!(c, challenge(Transcript::login("zkp_server", "Alice", 1)));
+        assert_ne!(c, challenge(Transcript::login("other_server", "Bob", 1)));
+        assert_ne!(
+            c,
+            challenge(Transcript::registration("zkp_server", "Bob", &[]))
+        );
+        assert_ne!(
+            challenge(Transcript::registration("zkp_server", "Bob", b"salt")),
+            challenge(Transcript::registration("zkp_server", "Bob", b"pepper"))
+        );
+        assert_ne!(
+            c,
+            Transcript::login("zkp_server", "Bob", 1).challenge(&group, (h, g), (h, g))
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Known-answer test vectors for the protocol, in a portable JSON format, so that implementations
+//! in other languages can check that they are compatible with this one.
+//!
+//! Every vector gives the secret `x`, the nonce `k` and the challenge `c`, the values computed from
+//! them - the public data (y1, y2) = (g^x, h^x), the commitment (r1, r2) = (g^k, h^k) and the
+//! answer s = k - c * x mod q - and whether the Verifier accepts them. In the vectors which are
+//! rejected, one of the values has been tampered with. The values are in the encoding of the
+//! group on the wire - decimal integers for the modp flavor, and for Ristretto255 the hex-encoded
+//! compressed points and (little-endian) scalars. The modp parameter sets are given as in a
+//! [`ParameterFile`].
+//!
+//! The scalars are derived from fixed labels, so `zkp-testvectors` always writes the same vectors
+//! (those committed in `zkp_core/test_vectors`). The format is versioned with [`VERSION`].
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    Error, Group, Nonce, ParameterFile, ParameterSet, Prover, Ristretto255Group, Verifier,
+};
+
+/// The version of the format of the test vectors
+pub const VERSION: u32 = 1;
+
+/// The domain separation tag for the derivation of the scalars of the vectors
+const DST: &[u8] = b"zkp_chaum_pedersen/test-vectors/v1";
+
+/// The name of the parameters of the Ristretto255 vectors
+const RISTRETTO255: &str = "ristretto255";
+
+/// What the Verifier makes of the values of a vector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expected {
+    Accept,
+    Reject,
+}
+
+/// A single known-answer test of the Prover and the Verifier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    pub description: String,
+    /// The name of the group - one of the modp parameter sets of the file, or `"ristretto255"`
+    pub params: String,
+    pub x: String,
+    pub k: String,
+    pub c: String,
+    pub y1: String,
+    pub y2: String,
+    pub r1: String,
+    pub r2: String,
+    pub s: String,
+    pub expected: Expected,
+}
+
+/// A file of test vectors
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVectors {
+    pub version: u32,
+    /// The modp parameter sets used by the vectors
+    pub parameters: Vec<ParameterFile>,
+    pub vectors: Vec<TestVector>,
+}
+
+impl TestVectors {
+    /// The vectors for every parameter set of the modp flavor, and for Ristretto255
+    pub fn generate() -> Self {
+        let mut parameters = Vec::new();
+        let mut vectors = Vec::new();
+
+        for set in ParameterSet::ALL {
+            let group = set.group();
+
+            parameters.push(ParameterFile::new(set.name(), set.seed(), &group));
+            vectors.extend(generate_for(&group, set.name()));
+        }
+        vectors.extend(generate_for(&Ristretto255Group::default(), RISTRETTO255));
+
+        TestVectors {
+            version: VERSION,
+            parameters,
+            vectors,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<TestVectors, Error> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|err| {
+            Error::InvalidTestVectors(format!("failed to read {}: {err}", path.display()))
+        })?;
+
+        TestVectors::parse(&contents)
+    }
+
+    /// Parse the (JSON) test vectors, which must be of the current [`VERSION`]
+    pub fn parse(contents: &str) -> Result<TestVectors, Error> {
+        let vectors: TestVectors = serde_json::from_str(contents)
+            .map_err(|err| Error::InvalidTestVectors(format!("failed to parse: {err}")))?;
+
+        if vectors.version != VERSION {
+            return Err(Error::InvalidTestVectors(format!(
+                "unsupported version {} (expected {VERSION})",
+                vectors.version
+            )));
+        }
+
+        Ok(vectors)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let path = path.as_ref();
+        let contents = serde_json::to_string_pretty(self).unwrap();
+
+        std::fs::write(path, contents + "\n").map_err(|err| {
+            Error::InvalidTestVectors(format!("failed to write {}: {err}", path.display()))
+        })
+    }
+
+    /// Check every vector against the Prover and the Verifier, failing on the first mismatch
+    pub fn check(&self) -> Result<(), Error> {
+        let groups = self
+            .parameters
+            .iter()
+            .map(|file| Ok((file.name.as_str(), file.group()?)))
+            .collect::<Result<HashMap<_, _>, Error>>()?;
+
+        for vector in &self.vectors {
+            match groups.get(vector.params.as_str()) {
+                Some(group) => check(group, vector)?,
+                None if vector.params == RISTRETTO255 => {
+                    check(&Ristretto255Group::default(), vector)?
+                }
+                None => {
+                    return Err(Error::InvalidTestVectors(format!(
+                        "{}: unknown parameters {:?}",
+                        vector.description, vector.params
+                    )))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A scalar derived from the label with SHA-256, as for the generators (see
+/// [`crate::generators`]) - the stream covers the bit length of q plus 128 bits, so that the
+/// scalars cover the whole range
+fn derive_scalar<G: Group>(group: &G, label: &str) -> G::Scalar {
+    let blocks = (group.order().bits() + 128).div_ceil(256) as u32;
+    let stream = (0..blocks)
+        .flat_map(|i| {
+            Sha256::new()
+                .chain_update(DST)
+                .chain_update(label)
+                .chain_update(i.to_be_bytes())
+                .finalize()
+        })
+        .collect::<Vec<u8>>();
+
+    group.scalar_from_bytes(&stream)
+}
+
+/// An honest proof over the group, and proofs with each of its values tampered with
+fn generate_for<G: Group>(group: &G, params: &str) -> Vec<TestVector> {
+    let scalar = |label: &str| derive_scalar(group, &format!("{params} {label}"));
+    let (x, k, c) = (scalar("x"), scalar("k"), scalar("c"));
+    let one = group.scalar_from_bytes(&[1]);
+
+    let prover = Prover::new(group.clone());
+    let nonce = Nonce::from_scalar(k.clone());
+    let (y1, y2) = prover.gen_public(&x);
+    let (r1, r2) = prover.gen_random(&nonce);
+    let s = prover.challenge_answer(&c, &nonce, &x).unwrap();
+
+    let honest = TestVector {
+        description: format!("{params}: honest proof"),
+        params: params.to_string(),
+        x: group.encode_scalar(&x),
+        k: group.encode_scalar(&k),
+        c: group.encode_scalar(&c),
+        y1: group.encode_element(&y1),
+        y2: group.encode_element(&y2),
+        r1: group.encode_element(&r1),
+        r2: group.encode_element(&r2),
+        s: group.encode_scalar(&s),
+        expected: Expected::Accept,
+    };
+    let tampered = |what: &str| TestVector {
+        description: format!("{params}: {what}"),
+        expected: Expected::Reject,
+        ..honest.clone()
+    };
+
+    vec![
+        honest.clone(),
+        TestVector {
+            s: group.encode_scalar(&group.scalar_mul_sub(&k, &c, &group.scalar_add(&x, &one))),
+            ..tampered("answer with the wrong secret, x + 1")
+        },
+        TestVector {
+            r1: group.encode_element(&group.mul(&r1, group.g())),
+            ..tampered("commitment r1 multiplied by g")
+        },
+        TestVector {
+            c: group.encode_scalar(&group.scalar_add(&c, &one)),
+            ..tampered("challenge c + 1")
+        },
+        TestVector {
+            y1: honest.y2.clone(),
+            y2: honest.y1.clone(),
+            ..tampered("public data (y1, y2) swapped")
+        },
+    ]
+}
+
+/// Check the vector against the Prover (for an accepted vector) and the Verifier
+fn check<G: Group>(group: &G, vector: &TestVector) -> Result<(), Error> {
+    let mismatch =
+        |reason: String| Error::InvalidTestVectors(format!("{}: {reason}", vector.description));
+    let element = |encoded: &str| {
+        group
+            .decode_element(encoded)
+            .map_err(|err| mismatch(err.to_string()))
+    };
+    let scalar = |encoded: &str| {
+        group
+            .decode_scalar(encoded)
+            .map_err(|err| mismatch(err.to_string()))
+    };
+
+    let (x, k, c, s) = (
+        scalar(&vector.x)?,
+        scalar(&vector.k)?,
+        scalar(&vector.c)?,
+        scalar(&vector.s)?,
+    );
+    let (y1, y2, r1, r2) = (
+        element(&vector.y1)?,
+        element(&vector.y2)?,
+        element(&vector.r1)?,
+        element(&vector.r2)?,
+    );
+
+    if vector.expected == Expected::Accept {
+        let prover = Prover::new(group.clone());
+        let nonce = Nonce::from_scalar(k);
+
+        if prover.gen_public(&x) != (y1.clone(), y2.clone()) {
+            return Err(mismatch("(y1, y2) is not (g^x, h^x)".into()));
+        }
+        if prover.gen_random(&nonce) != (r1.clone(), r2.clone()) {
+            return Err(mismatch("(r1, r2) is not (g^k, h^k)".into()));
+        }
+        if prover.challenge_answer(&c, &nonce, &x)? != s {
+            return Err(mismatch("s is not k - c * x".into()));
+        }
+    }
+
+    let accepted = Verifier::new(group.clone()).verify(&s, &c, &y1, &y2, &r1, &r2);
+    if accepted != (vector.expected == Expected::Accept) {
+        return Err(mismatch(format!(
+            "expected the Verifier to {:?}",
+            vector.expected
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The vectors committed to the repository
+    const COMMITTED: &str = include_str!("../test_vectors/chaum_pedersen_v1.json");
+
+    #[test]
+    fn test_committed_vectors() {
+        let vectors = TestVectors::parse(COMMITTED).unwrap();
+
+        assert_eq!(vectors.check(), Ok(()));
+        assert_eq!(vectors, TestVectors::generate());
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let vectors = TestVectors::parse(COMMITTED).unwrap();
+        let (honest, wrong_secret) = (&vectors.vectors[0], &vectors.vectors[1]);
+
+        let with_vector = |vector: TestVector| TestVectors {
+            vectors: vec![vector],
+            ..vectors.clone()
+        };
+
+        for vector in [
+            TestVector {
+                s: wrong_secret.s.clone(),
+                ..honest.clone()
+            },
+            TestVector {
+                expected: Expected::Accept,
+                ..wrong_secret.clone()
+            },
+            TestVector {
+                expected: Expected::Reject,
+                ..honest.clone()
+            },
+            TestVector {
+                y1: "forty-two".into(),
+                ..honest.clone()
+            },
+            TestVector {
+                params: "modp1024".into(),
+                ..honest.clone()
+            },
+        ] {
+            assert!(matches!(
+                with_vector(vector).check(),
+                Err(Error::InvalidTestVectors(_))
+            ));
+        }
+
+        let future = COMMITTED.replacen(
+            &format!("\"version\": {VERSION}"),
+            &format!("\"version\": {}", VERSION + 1),
+            1,
+        );
+        assert!(TestVectors::parse(&future).is_err());
+    }
+}
@@ -0,0 +1,515 @@
+use tracing::debug;
+
+use crate::prover::Proof;
+use crate::transcript::Transcript;
+use crate::{Error, Group, Prover};
+
+/// An (interactive) Chaum-Pedersen proof, i.e., the public data (y1, y2), the commitment
+/// (r1, r2), the challenge `c` and the answer `s`, to be verified as part of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchEntry<G: Group> {
+    pub y1: G::Element,
+    pub y2: G::Element,
+    pub r1: G::Element,
+    pub r2: G::Element,
+    pub c: G::Scalar,
+    pub s: G::Scalar,
+}
+
+/// The ZKP Chaum-Pedersen Verifier
+#[derive(Debug, Clone)]
+pub struct Verifier<G: Group> {
+    group: G,
+}
+
+impl<G: Group> Verifier<G> {
+    pub fn new(group: G) -> Self {
+        Verifier { group }
+    }
+
+    pub fn group(&self) -> &G {
+        &self.group
+    }
+
+    /// Check the group parameters (see [`Group::check`]), and run the protocol end to end with a
+    /// random secret, so that a misconfigured Verifier can refuse to start
+    pub fn self_test(&self) -> Result<(), Error> {
+        self.group.check()?;
+
+        let prover = Prover::new(self.group.clone());
+        let x = self.group.random_scalar();
+        let (y1, y2) = prover.gen_public(&x);
+
+        let k = prover.gen_nonce();
+        let (r1, r2) = prover.gen_random(&k);
+        let c = self.request_challenge();
+        let s = prover.challenge_answer(&c, &k, &x)?;
+
+        if !self.verify(&s, &c, &y1, &y2, &r1, &r2) {
+            return Err(Error::InvalidParameters(
+                "an honest proof did not verify".into(),
+            ));
+        }
+
+        let s = prover.challenge_answer(&c, &k, &self.group.random_scalar())?;
+
+        if self.verify(&s, &c, &y1, &y2, &r1, &r2) {
+            return Err(Error::InvalidParameters(
+                "a proof with the wrong secret verified".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// The `c` in the Chaum-Pedersen protocol (as per Smart)
+    pub fn request_challenge(&self) -> G::Scalar {
+        self.group.random_scalar()
+    }
+
+    /// Verify that the same password/secret as was used during the generation of (y1, y2), the
+    /// public data, is being used to generate the challenge response (from the client). This
+    /// verifies that the entered password is correct (or not).
+    ///
+    /// The answer `s` must be fully reduced (in [0, q)), otherwise it is rejected outright.
+    /// (r1, r2) are compared to (r1', r2') in constant time, and both comparisons are made.
+    pub fn verify(
+        &self,
+        s: &G::Scalar,
+        c: &G::Scalar,
+        y1: &G::Element,
+        y2: &G::Element,
+        r1: &G::Element,
+        r2: &G::Element,
+    ) -> bool {
+        debug!("s = {s:?}, c = {c:?}, y1: {y1:?}, y2: {y2:?}, r1 =  {r1:?}, r2: {r2:?}");
+
+        let group = &self.group;
+
+        if !group.scalar_is_reduced(s) {
+            debug!("s is not reduced modulo the group order");
+            return false;
+        }
+
+        let r1_prime = group.multi_exp(&[(group.g(), s), (y1, c)]);
+        let r2_prime = group.multi_exp(&[(group.h(), s), (y2, c)]);
+
+        debug!("r1_prime = {r1_prime:?}, r2_prime = {r2_prime:?}");
+
+        group.ct_eq(r1, &r1_prime) & group.ct_eq(r2, &r2_prime)
+    }
+
+    /// Verify many proofs at once, returning whether each of them is valid.
+    ///
+    /// Rather than checking r1 = g^s . y1^c and r2 = h^s . y2^c for every proof (four
+    /// exponentiations each), the checks are combined with random weights (a_i, b_i) into
+    ///
+    ///   g^(sum a_i s_i) . h^(sum b_i s_i) . prod (y1_i^(a_i c_i) . y2_i^(b_i c_i) .
+    ///   r1_i^(-a_i) . r2_i^(-b_i)) = 1
+    ///
+    /// which is computed with a single multi-exponentiation. A batch with an invalid proof only
+    /// passes with a negligible probability (about 1/q), in which case the proofs are checked one
+    /// by one to find the invalid ones.
+    ///
+    /// The elements must lie in the subgroup of order q, as for [`Verifier::verify`].
+    pub fn verify_batch(&self, entries: &[BatchEntry<G>]) -> Vec<bool> {
+        debug!("verifying a batch of {} proofs", entries.len());
+
+        if self.batch_holds(entries) {
+            return vec![true; entries.len()];
+        }
+
+        debug!("the batch did not verify, falling back to verifying each proof");
+
+        entries
+            .iter()
+            .map(|entry| {
+                self.verify(
+                    &entry.s, &entry.c, &entry.y1, &entry.y2, &entry.r1, &entry.r2,
+                )
+            })
+            .collect()
+    }
+
+    /// Whether the random linear combination of the checks of all the proofs holds
+    fn batch_holds(&self, entries: &[BatchEntry<G>]) -> bool {
+        let group = &self.group;
+
+        if !entries
+            .iter()
+            .all(|entry| group.scalar_is_reduced(&entry.s))
+        {
+            return false;
+        }
+
+        let weights = entries
+            .iter()
+            .map(|_| (group.random_scalar(), group.random_scalar()))
+            .collect::<Vec<_>>();
+
+        let zero = group.scalar_from_bytes(&[]);
+        let (mut g_exponent, mut h_exponent) = (zero.clone(), zero);
+        let mut exponents = Vec::with_capacity(4 * entries.len());
+
+        for (entry, (a, b)) in entries.iter().zip(&weights) {
+            g_exponent = group.scalar_add(&g_exponent, &group.scalar_mul(a, &entry.s));
+            h_exponent = group.scalar_add(&h_exponent, &group.scalar_mul(b, &entry.s));
+
+            exponents.push((&entry.y1, group.scalar_mul(a, &entry.c)));
+            exponents.push((&entry.y2, group.scalar_mul(b, &entry.c)));
+            exponents.push((&entry.r1, group.scalar_neg(a)));
+            exponents.push((&entry.r2, group.scalar_neg(b)));
+        }
+
+        let terms = [(group.g(), &g_exponent), (group.h(), &h_exponent)]
+            .into_iter()
+            .chain(exponents.iter().map(|(base, exponent)| (*base, exponent)))
+            .collect::<Vec<_>>();
+
+        group.ct_eq(&group.multi_exp(&terms), &group.identity())
+    }
+
+    /// Verify a non-interactive proof for the public data, (y1, y2), against the same transcript
+    /// as was used by the Prover
+    pub fn verify_proof(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        proof: &Proof<G>,
+        transcript: Transcript,
+    ) -> bool {
+        let c = transcript.challenge(&self.group, (y1, y2), (&proof.r1, &proof.r2));
+        self.verify(&proof.s, &c, y1, y2, &proof.r1, &proof.r2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+    use crate::{ModPGroup, Ristretto255Group};
+
+    #[test]
+    fn test_verify() {
+        let prover = Prover::new(ModPGroup::default());
+        let verifier = Verifier::new(ModPGroup::default());
+
+        let x = BigUint::from(987374283748273489u64);
+        let (y1, y2) = prover.gen_public(&x);
+
+        let k = prover.gen_nonce();
+        let (r1, r2) = prover.gen_random(&k);
+        let c = verifier.request_challenge();
+
+        let s = prover.challenge_answer(&c, &k, &x).unwrap();
+        assert!(verifier.verify(&s, &c, &y1, &y2, &r1, &r2));
+
+        let s = prover.challenge_answer(&c, &k, &(&x + 1u32)).unwrap();
+        assert!(!verifier.verify(&s, &c, &y1, &y2, &r1, &r2));
+
+        // s + q is congruent to s, but is not reduced
+        let s = prover.challenge_answer(&c, &k, &x).unwrap() + prover.group().q();
+        assert!(!verifier.verify(&s, &c, &y1, &y2, &r1, &r2));
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let prover = Prover::new(ModPGroup::default());
+        let verifier = Verifier::new(ModPGroup::default());
+
+        let x = prover.group().random_scalar();
+        let (y1, y2) = prover.gen_public(&x);
+
+        let proof = prover.prove(&x, Transcript::login("zkp_server", "Bob", 1000));
+        assert!(verifier.verify_proof(
+            &y1,
+            &y2,
+            &proof,
+            Transcript::login("zkp_server", "Bob", 1000)
+        ));
+
+        // the proof is bound to the user, the server, and the time
+        assert!(!verifier.verify_proof(
+            &y1,
+            &y2,
+            &proof,
+            Transcript::login("zkp_server", "Eve", 1000)
+        ));
+        assert!(!verifier.verify_proof(&y1, &y2, &proof, Transcript::login("other", "Bob", 1000)));
+        assert!(!verifier.verify_proof(
+            &y1,
+            &y2,
+            &proof,
+            Transcript::login("zkp_server", "Bob", 1001)
+        ));
+
+        let proof = prover.prove(&(&x + 1u32), Transcript::login("zkp_server", "Bob", 1000));
+        assert!(!verifier.verify_proof(
+            &y1,
+            &y2,
+            &proof,
+            Transcript::login("zkp_server", "Bob", 1000)
+        ));
+    }
+
+    fn batch<G: Group>(group: G, n: usize) -> Vec<BatchEntry<G>> {
+        let prover = Prover::new(group.clone());
+        let verifier = Verifier::new(group.clone());
+
+        (0..n)
+            .map(|_| {
+                let x = group.random_scalar();
+                let (y1, y2) = prover.gen_public(&x);
+
+                let k = prover.gen_nonce();
+                let (r1, r2) = prover.gen_random(&k);
+                let c = verifier.request_challenge();
+                let s = prover.challenge_answer(&c, &k, &x).unwrap();
+
+                BatchEntry {
+                    y1,
+                    y2,
+                    r1,
+                    r2,
+                    c,
+                    s,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let group = ModPGroup::default();
+        let verifier = Verifier::new(group.clone());
+
+        let mut entries = batch(group.clone(), 8);
+        assert_eq!(verifier.verify_batch(&entries), vec![true; 8]);
+        assert_eq!(verifier.verify_batch(&[]), Vec::<bool>::new());
+
+        // a wrong answer
+        entries[3].s = group.scalar_add(&entries[3].s, &BigUint::from(1u32));
+        // a commitment of another proof
+        entries[5].r2 = entries[6].r2.clone();
+
+        let mut expected = vec![true; 8];
+        (expected[3], expected[5]) = (false, false);
+        assert_eq!(verifier.verify_batch(&entries), expected);
+
+        // a valid proof, but with an unreduced answer
+        let mut entries = batch(group.clone(), 2);
+        entries[0].s += group.q();
+        assert_eq!(verifier.verify_batch(&entries), vec![false, true]);
+    }
+
+    #[test]
+    fn test_verify_batch_ristretto255() {
+        let group = Ristretto255Group::default();
+        let verifier = Verifier::new(group.clone());
+
+        let mut entries = batch(group.clone(), 16);
+        assert_eq!(verifier.verify_batch(&entries), vec![true; 16]);
+
+        entries[0].c = group.random_scalar();
+        let mut expected = vec![true; 16];
+        expected[0] = false;
+        assert_eq!(verifier.verify_batch(&entries), expected);
+    }
+
+    #[test]
+    fn test_self_test() {
+        let group = ModPGroup::default();
+        assert_eq!(Verifier::new(group.clone()).self_test(), Ok(()));
+
+        // h is the identity
+        let broken = ModPGroup::new(
+            group.p().clone(),
+            group.q().clone(),
+            group.g().clone(),
+            BigUint::from(1u32),
+        );
+        assert!(Verifier::new(broken).self_test().is_err());
+    }
+
+    /// Property tests of the protocol, over the groups of every parameter set
+    mod properties {
+        use proptest::prelude::*;
+
+        use super::*;
+        use crate::kdf::{password_to_secret, KdfParams};
+        use crate::{testing, Secret};
+
+        /// Cheap parameters, so that the tests run quickly
+        const KDF: KdfParams = KdfParams {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+
+        /// The nonce and the challenge of a run are derived from its seeds, so that a failing
+        /// case can be replayed
+        type Seeds = ([u8; 32], [u8; 32]);
+
+        /// How a proof is tampered with
+        #[derive(Debug, Clone, Copy)]
+        enum Tamper {
+            R1,
+            R2,
+            S,
+            C,
+            SwapY,
+        }
+
+        fn tamper() -> impl Strategy<Value = Tamper> {
+            prop_oneof![
+                Just(Tamper::R1),
+                Just(Tamper::R2),
+                Just(Tamper::S),
+                Just(Tamper::C),
+                Just(Tamper::SwapY),
+            ]
+        }
+
+        /// The public data of the password (registered with a fixed salt), and an honest proof
+        /// for it
+        fn run<G: Group>(group: &G, password: &str, (k, c): &Seeds) -> BatchEntry<G> {
+            let prover = Prover::new(group.clone());
+            let password = Secret::new(password.to_string());
+            let x = password_to_secret(group, &password, b"properties salt", &KDF).unwrap();
+            let (y1, y2) = prover.gen_public(x.expose());
+
+            let k = prover.derive_nonce_with(x.expose(), b"properties", k);
+            let (r1, r2) = prover.gen_random(&k);
+            let c = group.scalar_from_bytes(c);
+            let s = prover.challenge_answer(&c, &k, x.expose()).unwrap();
+
+            BatchEntry {
+                y1,
+                y2,
+                r1,
+                r2,
+                c,
+                s,
+            }
+        }
+
+        fn accepts<G: Group>(group: &G, entry: &BatchEntry<G>) -> bool {
+            Verifier::new(group.clone()).verify(
+                &entry.s, &entry.c, &entry.y1, &entry.y2, &entry.r1, &entry.r2,
+            )
+        }
+
+        fn honest<G: Group>(group: &G, password: &str, seeds: &Seeds) -> Result<(), TestCaseError> {
+            prop_assert!(accepts(group, &run(group, password, seeds)));
+            Ok(())
+        }
+
+        fn wrong_password<G: Group>(
+            group: &G,
+            (password, wrong): (&str, &str),
+            seeds: &Seeds,
+        ) -> Result<(), TestCaseError> {
+            let registered = run(group, password, seeds);
+            let login = BatchEntry {
+                y1: registered.y1,
+                y2: registered.y2,
+                ..run(group, wrong, seeds)
+            };
+
+            prop_assert!(!accepts(group, &login));
+            Ok(())
+        }
+
+        fn tampered<G: Group>(
+            group: &G,
+            password: &str,
+            seeds: &Seeds,
+            (tamper, delta): (Tamper, &[u8]),
+        ) -> Result<(), TestCaseError> {
+            let mut entry = run(group, password, seeds);
+            let delta = group.scalar_from_bytes(delta);
+            prop_assume!(delta != group.scalar_from_bytes(&[]));
+
+            match tamper {
+                Tamper::R1 => entry.r1 = group.mul(&entry.r1, &group.exp(group.g(), &delta)),
+                Tamper::R2 => entry.r2 = group.mul(&entry.r2, &group.exp(group.h(), &delta)),
+                Tamper::S => entry.s = group.scalar_add(&entry.s, &delta),
+                Tamper::C => entry.c = group.scalar_add(&entry.c, &delta),
+                Tamper::SwapY => std::mem::swap(&mut entry.y1, &mut entry.y2),
+            }
+
+            prop_assert!(!accepts(group, &entry));
+            Ok(())
+        }
+
+        fn round_trip<G: Group>(
+            group: &G,
+            password: &str,
+            seeds: &Seeds,
+        ) -> Result<(), TestCaseError> {
+            let entry = run(group, password, seeds);
+
+            for element in [&entry.y1, &entry.y2, &entry.r1, &entry.r2] {
+                let decoded = group.decode_element(&group.encode_element(element));
+                prop_assert_eq!(decoded.as_ref(), Ok(element));
+            }
+            for scalar in [&entry.c, &entry.s] {
+                let decoded = group.decode_scalar(&group.encode_scalar(scalar));
+                prop_assert_eq!(decoded.as_ref(), Ok(scalar));
+                prop_assert_eq!(
+                    &group.scalar_from_bytes(&group.scalar_to_bytes(scalar)),
+                    scalar
+                );
+            }
+            Ok(())
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(16))]
+
+            #[test]
+            fn test_honest_proofs_verify(password in ".{0,32}", seeds in any::<Seeds>()) {
+                for group in testing::groups() {
+                    honest(group, &password, &seeds)?;
+                }
+                honest(&Ristretto255Group::default(), &password, &seeds)?;
+            }
+
+            #[test]
+            fn test_wrong_password_never_verifies(
+                password in ".{0,32}",
+                wrong in ".{0,32}",
+                seeds in any::<Seeds>(),
+            ) {
+                prop_assume!(password != wrong);
+
+                for group in testing::groups() {
+                    wrong_password(group, (&password, &wrong), &seeds)?;
+                }
+                wrong_password(&Ristretto255Group::default(), (&password, &wrong), &seeds)?;
+            }
+
+            #[test]
+            fn test_tampered_proofs_never_verify(
+                password in ".{0,32}",
+                seeds in any::<Seeds>(),
+                tamper in tamper(),
+                delta in any::<[u8; 32]>(),
+            ) {
+                for group in testing::groups() {
+                    tampered(group, &password, &seeds, (tamper, &delta))?;
+                }
+                tampered(&Ristretto255Group::default(), &password, &seeds, (tamper, &delta))?;
+            }
+
+            #[test]
+            fn test_encoding_round_trips(password in ".{0,32}", seeds in any::<Seeds>()) {
+                for group in testing::groups() {
+                    round_trip(group, &password, &seeds)?;
+                }
+                round_trip(&Ristretto255Group::default(), &password, &seeds)?;
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use tracing::info;
+
+use zkp_core::{generators, primes, Group, ParameterFile};
+
+const USAGE: &str = "\
+Usage: zkp-paramgen [--bits <BITS>] [--name <NAME>] [--seed <SEED>] [--output <PATH>]
+
+Generate a custom parameter set for the modp flavor - a safe prime p = 2q + 1 of the requested bit
+length, with generators of order q derived from the seed - and write it to a parameter file that
+the ZKP client and server load via ZKP_PARAMS_FILE.
+
+Options:
+  --bits <BITS>    The bit length of p [default: 2048]
+  --name <NAME>    The name of the parameter set [default: custom]
+  --seed <SEED>    The public seed for the generators [default: \"zkp_chaum_pedersen <NAME>\"]
+  --output <PATH>  The parameter file to write [default: params.json]";
+
+/// The command-line options for the parameter generation tool
+#[derive(Debug)]
+struct Options {
+    bits: u64,
+    name: String,
+    seed: Option<String>,
+    output: String,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            bits: 2048,
+            name: "custom".into(),
+            seed: None,
+            output: "params.json".into(),
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+
+            match arg.as_str() {
+                "--bits" => {
+                    options.bits = value()?
+                        .parse()
+                        .map_err(|_| "--bits must be a number".to_string())?;
+                }
+                "--name" => options.name = value()?,
+                "--seed" => options.seed = Some(value()?),
+                "--output" => options.output = value()?,
+                _ => return Err(format!("unknown option {arg}")),
+            }
+        }
+
+        if options.bits < 16 {
+            return Err("--bits must be at least 16".into());
+        }
+
+        Ok(options)
+    }
+}
+
+/// The entrypoint for the parameter generation tool
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
+
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(reason) => {
+            eprintln!("{reason}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let seed = options
+        .seed
+        .unwrap_or_else(|| format!("zkp_chaum_pedersen {}", options.name));
+
+    info!("Generating a {}-bit safe prime", options.bits);
+    let (p, q) = primes::gen_safe_prime(options.bits);
+
+    info!("Deriving the generators from the seed {seed:?}");
+    let group = generators::group_from_seed(p, q, seed.as_bytes());
+
+    info!("Checking the parameters");
+    group.check()?;
+
+    ParameterFile::new(&options.name, &seed, &group).save(&options.output)?;
+
+    info!(
+        "Wrote the {:?} parameter set to {}",
+        options.name, options.output
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        let options = parse(&["--bits", "512", "--name", "test", "--output", "test.json"]).unwrap();
+
+        assert_eq!(options.bits, 512);
+        assert_eq!(options.name, "test");
+        assert_eq!(options.seed, None);
+        assert_eq!(options.output, "test.json");
+
+        assert!(parse(&["--bits"]).is_err());
+        assert!(parse(&["--bits", "8"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+}
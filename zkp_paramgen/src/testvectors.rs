@@ -0,0 +1,99 @@
+use tracing::info;
+
+use zkp_core::TestVectors;
+
+const USAGE: &str = "\
+Usage: zkp-testvectors [--output <PATH>] [--check <PATH>]
+
+Generate the known-answer test vectors of the protocol - for every parameter set of the modp
+flavor, and for Ristretto255 - and write them to a JSON file, or check the vectors of a file
+(e.g., one written by another implementation) against this one.
+
+Options:
+  --output <PATH>  The file to write the vectors to [default: test_vectors.json]
+  --check <PATH>   Check the vectors of the file, rather than generating them";
+
+/// The command-line options for the test vector tool
+#[derive(Debug)]
+struct Options {
+    output: String,
+    check: Option<String>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            output: "test_vectors.json".into(),
+            check: None,
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+
+            match arg.as_str() {
+                "--output" => options.output = value()?,
+                "--check" => options.check = Some(value()?),
+                _ => return Err(format!("unknown option {arg}")),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// The entrypoint for the test vector tool
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::fmt::init();
+
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(reason) => {
+            eprintln!("{reason}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    if let Some(path) = options.check {
+        let vectors = TestVectors::load(&path)?;
+        vectors.check()?;
+
+        info!("All {} vectors of {path} match", vectors.vectors.len());
+        return Ok(());
+    }
+
+    info!("Generating the test vectors");
+    let vectors = TestVectors::generate();
+    vectors.save(&options.output)?;
+
+    info!(
+        "Wrote {} vectors to {}",
+        vectors.vectors.len(),
+        options.output
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        let options = parse(&["--output", "vectors.json"]).unwrap();
+
+        assert_eq!(options.output, "vectors.json");
+        assert_eq!(options.check, None);
+        assert_eq!(
+            parse(&["--check", "vectors.json"]).unwrap().check,
+            Some("vectors.json".into())
+        );
+
+        assert!(parse(&["--check"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+}
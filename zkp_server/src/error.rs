@@ -0,0 +1,91 @@
+use std::fmt;
+
+use tonic::{Code, Status};
+
+/// Errors raised while handling the requests of the Auth service - every error maps to a gRPC
+/// status code, so that a malformed request is answered with a status instead of a panic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// The field could not be decoded
+    InvalidEncoding(&'static str),
+    /// The field was decoded, but is not a valid group element (the reason is the error of
+    /// [`zkp_core::Group::validate_element`])
+    InvalidElement(&'static str, zkp_core::Error),
+    /// The scalar field is not fully reduced modulo the group order
+    UnreducedScalar(&'static str),
+    /// The field is required, but is missing
+    MissingField(&'static str),
+    /// The KDF parameters of the registration are not accepted by the KDF
+    InvalidKdfParameters(zkp_core::Error),
+    /// The proof of knowledge of the secret, sent at registration, did not verify
+    InvalidRegistrationProof,
+    /// The commitment (r1, r2) has already been used by the user - answering another challenge
+    /// for it would reveal the secret
+    ReusedCommitment,
+    /// The user has not been registered
+    UnregisteredUser,
+    /// The `auth_id` does not match any pending challenge - it is unknown, or its challenge has
+    /// already been answered
+    UnknownAuthId,
+    /// The challenge was not answered within its time to live
+    ExpiredChallenge,
+    /// The user has too many challenges pending
+    TooManyPendingChallenges,
+    /// The timestamp of the login proof is too far from the clock of the server
+    StaleProof,
+    /// The proof did not verify
+    AuthenticationFailed,
+}
+
+impl AuthError {
+    pub fn code(&self) -> Code {
+        match self {
+            AuthError::InvalidEncoding(_)
+            | AuthError::InvalidElement(..)
+            | AuthError::UnreducedScalar(_)
+            | AuthError::MissingField(_)
+            | AuthError::InvalidKdfParameters(_)
+            | AuthError::InvalidRegistrationProof
+            | AuthError::ReusedCommitment => Code::InvalidArgument,
+            AuthError::UnregisteredUser | AuthError::UnknownAuthId => Code::NotFound,
+            AuthError::TooManyPendingChallenges => Code::ResourceExhausted,
+            AuthError::ExpiredChallenge
+            | AuthError::StaleProof
+            | AuthError::AuthenticationFailed => Code::Unauthenticated,
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::InvalidEncoding(field) => write!(f, "failed to extract {field}"),
+            AuthError::InvalidElement(field, err) => {
+                write!(f, "failed to validate {field} - {err}")
+            }
+            AuthError::UnreducedScalar(field) => {
+                write!(f, "{field} is not reduced modulo the group order")
+            }
+            AuthError::MissingField(field) => write!(f, "missing {field}"),
+            AuthError::InvalidKdfParameters(err) => write!(f, "{err}"),
+            AuthError::InvalidRegistrationProof => write!(f, "registration proof did not verify"),
+            AuthError::ReusedCommitment => write!(f, "commitment (r1, r2) has already been used"),
+            AuthError::UnregisteredUser => write!(f, "user is not registered"),
+            AuthError::UnknownAuthId => write!(f, "unknown auth_id"),
+            AuthError::ExpiredChallenge => write!(f, "challenge has expired"),
+            AuthError::TooManyPendingChallenges => {
+                write!(f, "too many pending challenges for the user")
+            }
+            AuthError::StaleProof => write!(f, "login proof is stale"),
+            AuthError::AuthenticationFailed => write!(f, "user authentication failed"),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl From<AuthError> for Status {
+    fn from(err: AuthError) -> Self {
+        Status::new(err.code(), err.to_string())
+    }
+}
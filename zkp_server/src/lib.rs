@@ -1,185 +1,354 @@
-use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use lazy_static::lazy_static;
 use num_bigint::BigInt;
-use tonic::{Code, Request, Response, Status};
-use tracing::{debug, info};
+use sha2::{Digest, Sha256};
+use tonic::{Request, Response, Status};
+use tracing::{debug, error, info};
+use zkp_core::kdf::MIN_SALT_LEN;
+use zkp_core::transcript::DEFAULT_SERVER_ID;
+use zkp_core::{gen_random_with_n_bits, BatchEntry, Group, KdfParams, Proof, Transcript};
 
 use crate::zkp_auth::{
-    auth_server::Auth, AuthenticationAnswerRequest, AuthenticationAnswerResponse,
-    AuthenticationChallengeRequest, AuthenticationChallengeResponse, RegisterRequest,
-    RegisterResponse,
+    auth_server::Auth, AuthenticationAnswerBatchRequest, AuthenticationAnswerBatchResponse,
+    AuthenticationAnswerRequest, AuthenticationAnswerResponse, AuthenticationAnswerResult,
+    AuthenticationChallengeRequest, AuthenticationChallengeResponse, LoginParametersRequest,
+    LoginParametersResponse, LoginWithProofRequest, RegisterRequest, RegisterResponse,
+    StatusRequest, StatusResponse,
 };
 
-pub mod zkp_verifier {
-    use num_bigint::{BigInt, RandomBits};
-    use num_integer::Integer;
-    use num_traits::{identities::Zero, One, Signed};
-    use once_cell::sync::OnceCell;
-    use rand::Rng;
-    use tracing::debug;
+/// The maximum difference (in seconds) between the timestamp of a login proof and the clock of
+/// the server - proofs outside of this window are rejected as stale
+const MAX_CLOCK_SKEW_SECS: u64 = 30;
 
-    static P: OnceCell<BigInt> = OnceCell::new();
-    static G: OnceCell<BigInt> = OnceCell::new();
-    static H: OnceCell<BigInt> = OnceCell::new();
+/// The time (in seconds) a challenge may stay pending before it is answered
+const CHALLENGE_TTL_SECS: u64 = MAX_CLOCK_SKEW_SECS;
 
-    fn get_p() -> &'static BigInt {
-        P.get().unwrap()
-    }
+/// The time (in seconds) a commitment (r1, r2) is remembered for - far longer than a challenge
+/// is pending or a login proof is accepted, so that a client which repeats its nonce `k` across
+/// logins (which would reveal `x`) is still caught long after the first login
+const COMMITMENT_TTL_SECS: u64 = 24 * 60 * 60;
 
-    fn get_g() -> &'static BigInt {
-        G.get().unwrap()
-    }
+/// The maximum number of challenges pending for a user at any time
+const MAX_PENDING_CHALLENGES: usize = 16;
 
-    fn get_h() -> &'static BigInt {
-        H.get().unwrap()
-    }
+/// The current time, in seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Verifier state
+#[derive(Debug, Clone)]
+struct VerifierUserState<G: Group> {
+    y1: G::Element,
+    y2: G::Element,
+    /// the salt and the cost parameters of the KDF, which the Prover needs to derive `x` from the
+    /// password
+    salt: Vec<u8>,
+    kdf: KdfParams,
+    /// the commitments (r1, r2) used recently by the user - a commitment is only accepted once,
+    /// since two answers for it (to different challenges) reveal the secret `x`
+    recent_commitments: RecentCommitments,
+}
 
-    pub fn gen_random_with_n_bits<const N: u64>() -> BigInt {
-        let mut rng = rand::thread_rng();
-        rng.sample::<BigInt, _>(RandomBits::new(N)).abs()
+impl<G: Group> VerifierUserState<G> {
+    fn new(y1: G::Element, y2: G::Element, salt: Vec<u8>, kdf: KdfParams) -> Self {
+        VerifierUserState {
+            y1,
+            y2,
+            salt,
+            kdf,
+            recent_commitments: RecentCommitments::default(),
+        }
     }
+}
 
-    // source: https://medium.com/asecuritysite-when-bob-met-alice/to-the-builders-of-our-future-meet-the-chaum-pedersen-non-interactive-zero-knowledge-proof-method-9846dee47fbc
-    fn get_extended_euclidean(b: &BigInt, phi: &BigInt) -> BigInt {
-        let mut u = vec![BigInt::one(), BigInt::zero(), phi.clone()];
-        let mut v = vec![BigInt::zero(), BigInt::one(), b.clone()];
+/// The digests of the commitments (r1, r2) seen recently, each remembered for
+/// [`COMMITMENT_TTL_SECS`] - they are forgotten by time only, so no number of requests can flush
+/// a commitment out early
+#[derive(Debug, Clone, Default)]
+struct RecentCommitments {
+    digests: HashSet<[u8; 32]>,
+    /// the digests with the time they were seen, oldest first
+    seen_at: VecDeque<(u64, [u8; 32])>,
+}
 
-        while v[2] != BigInt::zero() {
-            let q = &u[2] / &v[2];
-            let temp1 = &u[0] - &q * &v[0];
-            let temp2 = &u[1] - &q * &v[1];
-            let temp3 = &u[2] - &q * &v[2];
+impl RecentCommitments {
+    /// Remember the commitment, or fail if it has been seen recently
+    fn insert(&mut self, digest: [u8; 32]) -> Result<(), AuthError> {
+        let now = now();
 
-            u[0] = std::mem::take(&mut v[0]);
-            u[1] = std::mem::take(&mut v[1]);
-            u[2] = std::mem::take(&mut v[2]);
-            v[0] = temp1;
-            v[1] = temp2;
-            v[2] = temp3;
+        while let Some(&(seen_at, expired)) = self.seen_at.front() {
+            if seen_at + COMMITMENT_TTL_SECS >= now {
+                break;
+            }
+            self.digests.remove(&expired);
+            self.seen_at.pop_front();
         }
 
-        if u[1] < BigInt::zero() {
-            &u[1] + phi
-        } else {
-            std::mem::take(&mut u[1])
+        if !self.digests.insert(digest) {
+            return Err(AuthError::ReusedCommitment);
         }
-    }
+        self.seen_at.push_back((now, digest));
 
-    /// Initialise the ZKP Verifier
-    pub fn init() {
-        let _ = P.set(BigInt::from(2u32).pow(255) - BigInt::from(19u32));
-        let _ = G.set(BigInt::from(5u32));
-        let _ = H.set(BigInt::from(3u32));
+        Ok(())
     }
+}
 
-    /// The `c` in the Chaum-Pedersen protocol (as per Smart)
-    pub fn request_challenge() -> BigInt {
-        gen_random_with_n_bits::<128>()
-    }
+/// A challenge issued to a user, until it is answered
+#[derive(Debug, Clone)]
+struct PendingChallenge<G: Group> {
+    user: String,
+    r1: G::Element,
+    r2: G::Element,
+    c: G::Scalar,
+    /// when the challenge was issued, in seconds since the Unix epoch
+    issued_at: u64,
+}
 
-    /// Verify that the same password/secret as was used during the generation of (y1, y2), the
-    /// public data, is being used to generate the challenge response (from the client). This
-    /// verifies that the entered password is correct (or not).
-    pub fn verify(
-        s: &BigInt,
-        c: &BigInt,
-        y1: &BigInt,
-        y2: &BigInt,
-        r1: &BigInt,
-        r2: &BigInt,
-    ) -> bool {
-        debug!("s = {s:?}, c = {c:?}, y1: {y1:?}, y2: {y2:?}, r1 =  {r1:?}, r2: {r2:?}");
+/// The challenges pending an answer, by `auth_id`, and indexed by user - so that the challenges
+/// of a user are counted (and expired) without going through those of every other user
+#[derive(Debug)]
+struct PendingChallenges<G: Group> {
+    by_auth_id: HashMap<BigInt, PendingChallenge<G>>,
+    /// the `auth_id`s of the pending challenges of every user, oldest first
+    by_user: HashMap<String, VecDeque<BigInt>>,
+}
 
-        let (val1, val2) = if *s < BigInt::zero() {
-            let v1 = get_g().modpow(&-s, get_p());
-            let v2 = get_h().modpow(&-s, get_p());
+impl<G: Group> Default for PendingChallenges<G> {
+    fn default() -> Self {
+        PendingChallenges {
+            by_auth_id: HashMap::new(),
+            by_user: HashMap::new(),
+        }
+    }
+}
 
-            (
-                get_extended_euclidean(&v1, &get_p()),
-                get_extended_euclidean(&v2, &get_p()),
-            )
-        } else {
-            (get_g().modpow(&s, get_p()), get_h().modpow(&s, get_p()))
+impl<G: Group> PendingChallenges<G> {
+    /// Drop the expired challenges of the user, and return the number of those still pending
+    fn expire(&mut self, user: &str, now: u64) -> usize {
+        let Some(auth_ids) = self.by_user.get_mut(user) else {
+            return 0;
         };
 
-        let (val3, val4) = if *c < BigInt::zero() {
-            let v1 = y1.modpow(&c, &get_p());
-            let v2 = y2.modpow(&c, &get_p());
+        while let Some(auth_id) = auth_ids.front() {
+            if self.by_auth_id[auth_id].issued_at + CHALLENGE_TTL_SECS >= now {
+                break;
+            }
+            self.by_auth_id.remove(auth_id);
+            auth_ids.pop_front();
+        }
 
-            (
-                get_extended_euclidean(&v1, &get_p()),
-                get_extended_euclidean(&v2, &get_p()),
-            )
-        } else {
-            (y1.modpow(&c, get_p()), y2.modpow(&c, get_p()))
-        };
+        let pending = auth_ids.len();
+        if pending == 0 {
+            self.by_user.remove(user);
+        }
+
+        pending
+    }
 
-        let r1_prime = (val1 * val3).mod_floor(get_p());
-        let r2_prime = (val2 * val4).mod_floor(get_p());
+    fn insert(&mut self, auth_id: BigInt, challenge: PendingChallenge<G>) {
+        self.by_user
+            .entry(challenge.user.clone())
+            .or_default()
+            .push_back(auth_id.clone());
+        self.by_auth_id.insert(auth_id, challenge);
+    }
 
-        debug!("r1 = {:?}, r2 = {:?}", r1, r2);
-        debug!("r1_prime = {r1_prime:?}, r2_prime = {r2_prime:?}");
+    fn remove(&mut self, auth_id: &BigInt) -> Option<PendingChallenge<G>> {
+        let challenge = self.by_auth_id.remove(auth_id)?;
 
-        *r1 == r1_prime && *r2 == r2_prime
+        if let Entry::Occupied(mut auth_ids) = self.by_user.entry(challenge.user.clone()) {
+            auth_ids.get_mut().retain(|pending| pending != auth_id);
+            if auth_ids.get().is_empty() {
+                auth_ids.remove();
+            }
+        }
+
+        Some(challenge)
     }
+}
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+pub mod error;
 
-        #[test]
-        fn test_init() {
-            init();
+pub use error::AuthError;
 
-            assert_eq!(*get_p(), BigInt::from(2u32).pow(255) - BigInt::from(19u32));
-            assert_eq!(*get_g(), BigInt::from(5u32));
-            assert_eq!(*get_h(), BigInt::from(3u32));
+/// Wrapper module for the Auth protocol buffer definition
+pub mod zkp_auth {
+    tonic::include_proto!("zkp_auth");
+}
+
+impl From<zkp_auth::KdfParams> for KdfParams {
+    fn from(params: zkp_auth::KdfParams) -> Self {
+        KdfParams {
+            memory_kib: params.memory_kib,
+            iterations: params.iterations,
+            parallelism: params.parallelism,
         }
     }
 }
 
-/// Verifier state
-#[derive(Debug, Default, Clone)]
-struct VerifierUserState {
-    y1: BigInt,
-    y2: BigInt,
-    r1: Option<BigInt>,
-    r2: Option<BigInt>,
-    c: Option<BigInt>,
+impl From<KdfParams> for zkp_auth::KdfParams {
+    fn from(params: KdfParams) -> Self {
+        zkp_auth::KdfParams {
+            memory_kib: params.memory_kib,
+            iterations: params.iterations,
+            parallelism: params.parallelism,
+        }
+    }
 }
 
-// Registered Users
-// In production, this would be a database instead.
-lazy_static! {
+/// Wrapper struct for interacting with the gRPC code generated by tonic, generic over the group
+/// (and hence the flavor of the protocol) used by the ZKP Verifier.
+#[derive(Debug)]
+pub struct Verifier<G: Group> {
+    zkp_verifier: zkp_core::Verifier<G>,
+
+    // Registered Users
+    // In production, this would be a database instead.
     /// maps the users against the state needed for the Chaum-Pedersen protocol - this
     /// is needed since gRPC is stateless, and we need the state to persist across
     /// the request steps.
-    static ref REGISTERED_USERS: Mutex<HashMap<String, VerifierUserState>> = {
-        let m = Mutex::new(HashMap::new());
-        m
-    };
-
-    /// Maps the `auth_id` generated by the server (and which is sent to the client), so that we
-    /// can match it in the challenge verification step.
-    static ref AUTH_ID_USER_MAP: Mutex<HashMap<BigInt, String>> = {
-        let m = Mutex::new(HashMap::new());
-        m
-    };
+    registered_users: Mutex<HashMap<String, VerifierUserState<G>>>,
+
+    /// Maps the `auth_id` generated by the server (and which is sent to the client) to the
+    /// challenge it was issued for, so that we can match it in the challenge verification step.
+    /// A challenge is removed as soon as it is answered, so it is never verified twice, and
+    /// expires after [`CHALLENGE_TTL_SECS`].
+    pending_challenges: Mutex<PendingChallenges<G>>,
+
+    /// The result of the self-test of the group parameters (`None` if it has not been run)
+    self_test_result: Option<Result<(), zkp_core::Error>>,
+
+    /// The identity of the server, which is bound into the transcripts of login proofs
+    server_id: String,
 }
 
-/// Wrapper module for the Auth protocol buffer definition
-pub mod zkp_auth {
-    tonic::include_proto!("zkp_auth");
+impl<G: Group> Verifier<G> {
+    pub fn new(group: G) -> Self {
+        Verifier {
+            zkp_verifier: zkp_core::Verifier::new(group),
+            registered_users: Mutex::new(HashMap::new()),
+            pending_challenges: Mutex::new(PendingChallenges::default()),
+            self_test_result: None,
+            server_id: DEFAULT_SERVER_ID.to_string(),
+        }
+    }
+
+    pub fn with_server_id(mut self, server_id: String) -> Self {
+        self.server_id = server_id;
+        self
+    }
+
+    /// Run the self-test of the group parameters (see [`zkp_core::Verifier::self_test`]) - the
+    /// server must not be started if this fails. The result is also reported by `GetStatus`.
+    pub fn self_test(&mut self) -> Result<(), zkp_core::Error> {
+        let description = self.zkp_verifier.group().description();
+        let result = self.zkp_verifier.self_test();
+
+        match &result {
+            Ok(()) => info!("[Auth Server] Self-test of the {description} group passed"),
+            Err(err) => error!("[Auth Server] Self-test of the {description} group failed: {err}"),
+        }
+
+        self.self_test_result = Some(result.clone());
+        result
+    }
+
+    /// Decode a group element received from the client, and check that it is a valid element
+    /// of the group (see [`Group::validate_element`])
+    fn decode_element(&self, field: &'static str, encoded: &str) -> Result<G::Element, AuthError> {
+        let group = self.zkp_verifier.group();
+
+        let element = group
+            .decode_element(encoded)
+            .map_err(|_| AuthError::InvalidEncoding(field))?;
+
+        group
+            .validate_element(&element)
+            .map_err(|err| AuthError::InvalidElement(field, err))?;
+
+        Ok(element)
+    }
+
+    /// The digest of the commitment (r1, r2), by which it is remembered
+    fn commitment_digest(&self, r1: &G::Element, r2: &G::Element) -> [u8; 32] {
+        let group = self.zkp_verifier.group();
+
+        Sha256::new()
+            .chain_update(group.encode_element(r1))
+            .chain_update(",")
+            .chain_update(group.encode_element(r2))
+            .finalize()
+            .into()
+    }
+
+    /// Decode a scalar received from the client, and check that it is fully reduced
+    fn decode_scalar(&self, field: &'static str, encoded: &str) -> Result<G::Scalar, AuthError> {
+        let group = self.zkp_verifier.group();
+
+        let scalar = group
+            .decode_scalar(encoded)
+            .map_err(|_| AuthError::InvalidEncoding(field))?;
+
+        if !group.scalar_is_reduced(&scalar) {
+            return Err(AuthError::UnreducedScalar(field));
+        }
+
+        Ok(scalar)
+    }
+
+    /// Match the answer against the pending challenge of its `auth_id`, for verification - the
+    /// challenge is consumed, so that the answer cannot be replayed (whether it verifies or not)
+    fn pending_answer(
+        &self,
+        answer: &AuthenticationAnswerRequest,
+    ) -> Result<BatchEntry<G>, AuthError> {
+        let auth_id = BigInt::parse_bytes(answer.auth_id.as_bytes(), 10)
+            .ok_or(AuthError::InvalidEncoding("auth_id"))?;
+
+        let s = self.decode_scalar("s", &answer.s)?;
+
+        let challenge = self
+            .pending_challenges
+            .lock()
+            .unwrap()
+            .remove(&auth_id)
+            .ok_or(AuthError::UnknownAuthId)?;
+
+        if challenge.issued_at + CHALLENGE_TTL_SECS < now() {
+            return Err(AuthError::ExpiredChallenge);
+        }
+
+        let (y1, y2) = match self.registered_users.lock().unwrap().get(&challenge.user) {
+            Some(state) => (state.y1.clone(), state.y2.clone()),
+            None => return Err(AuthError::UnregisteredUser),
+        };
+
+        Ok(BatchEntry {
+            y1,
+            y2,
+            r1: challenge.r1,
+            r2: challenge.r2,
+            c: challenge.c,
+            s,
+        })
+    }
 }
 
-/// Wrapper struct for interacting with the gRPC code generated by tonic
-#[derive(Debug, Default)]
-pub struct Verifier {}
+impl<G: Group + Default> Default for Verifier<G> {
+    fn default() -> Self {
+        Verifier::new(G::default())
+    }
+}
 
 #[tonic::async_trait]
-impl Auth for Verifier {
+impl<G: Group> Auth for Verifier<G> {
     /// Register the user with the system
     async fn register(
         &self,
@@ -190,28 +359,44 @@ impl Auth for Verifier {
 
         let request = request.into_inner();
 
-        // if the call came from a different client, such as grpcurl, for instance
-        if REGISTERED_USERS.lock().unwrap().contains_key(&request.user) {
-            return Ok(Response::new(zkp_auth::RegisterResponse {}));
-        }
-
-        let y1 = BigInt::parse_bytes(request.y1.as_bytes(), 10)
-            .ok_or(Status::new(Code::InvalidArgument, "failed to extract y1"))?;
+        // an identity (or an element outside the subgroup) would let anyone log in as the user
+        let y1 = self.decode_element("y1", &request.y1)?;
+        let y2 = self.decode_element("y2", &request.y2)?;
 
-        let y2 = BigInt::parse_bytes(request.y2.as_bytes(), 10)
-            .ok_or(Status::new(Code::InvalidArgument, "failed to extract y2"))?;
+        let salt = hex::decode(&request.salt)
+            .ok()
+            .filter(|salt| salt.len() >= MIN_SALT_LEN)
+            .ok_or(AuthError::InvalidEncoding("salt"))?;
 
-        REGISTERED_USERS.lock().unwrap().insert(
-            request.user,
-            VerifierUserState {
-                y1,
-                y2,
-                ..VerifierUserState::default()
-            },
+        let kdf = KdfParams::from(
+            request
+                .kdf
+                .ok_or(AuthError::MissingField("KDF parameters"))?,
         );
+        kdf.check().map_err(AuthError::InvalidKdfParameters)?;
+
+        // the registrant must prove that it knows x, with y1 = g^x and y2 = h^x
+        let proof = Proof {
+            r1: self.decode_element("r1", &request.r1)?,
+            r2: self.decode_element("r2", &request.r2)?,
+            s: self.decode_scalar("s", &request.s)?,
+        };
+        let transcript = Transcript::registration(&self.server_id, &request.user, &salt);
+
+        if !self.zkp_verifier.verify_proof(&y1, &y2, &proof, transcript) {
+            return Err(AuthError::InvalidRegistrationProof.into());
+        }
 
-        // initialise the verifier
-        zkp_verifier::init();
+        // the request is checked before taking the lock, which every login also takes - the check
+        // for an existing user and the insert are done under the same lock, so that two
+        // concurrent registrations of the user cannot both pass the check
+        match self.registered_users.lock().unwrap().entry(request.user) {
+            // if the call came from a different client, such as grpcurl, for instance
+            Entry::Occupied(_) => {}
+            Entry::Vacant(entry) => {
+                entry.insert(VerifierUserState::new(y1, y2, salt, kdf));
+            }
+        }
 
         Ok(Response::new(zkp_auth::RegisterResponse {}))
     }
@@ -225,38 +410,59 @@ impl Auth for Verifier {
         debug!("[Auth Server] authentication challenge request payload: {request:?}");
 
         let request = request.into_inner();
+        let group = self.zkp_verifier.group();
         let (user, r1, r2) = (
             request.user,
-            BigInt::parse_bytes(request.r1.as_bytes(), 10).unwrap(),
-            BigInt::parse_bytes(request.r2.as_bytes(), 10).unwrap(),
+            self.decode_element("r1", &request.r1)?,
+            self.decode_element("r2", &request.r2)?,
         );
 
-        // ensure that the user has been registered
-        if !REGISTERED_USERS.lock().unwrap().contains_key(&user) {
-            return Err(Status::new(Code::NotFound, "user is not registered"));
+        let auth_id = gen_random_with_n_bits::<128>();
+        let challenge = self.zkp_verifier.request_challenge();
+        let issued_at = now();
+
+        // challenges which are never answered expire, and a user only has a few pending at once,
+        // so that unanswered challenges cannot pile up
+        let mut pending_challenges = self.pending_challenges.lock().unwrap();
+
+        if pending_challenges.expire(&user, issued_at) >= MAX_PENDING_CHALLENGES {
+            return Err(AuthError::TooManyPendingChallenges.into());
         }
 
-        let auth_id = zkp_verifier::gen_random_with_n_bits::<128>();
-        let challenge = zkp_verifier::request_challenge();
+        // ensure that the user has been registered, and that the commitment is fresh - a second
+        // challenge for the same commitment would let an eavesdropper extract `x`
+        let (salt, kdf) = {
+            let mut registered_users = self.registered_users.lock().unwrap();
+            let state = registered_users
+                .get_mut(&user)
+                .ok_or(AuthError::UnregisteredUser)?;
 
-        let reply = zkp_auth::AuthenticationChallengeResponse {
-            auth_id: auth_id.clone().to_string(),
-            c: challenge.clone().to_string(),
+            state
+                .recent_commitments
+                .insert(self.commitment_digest(&r1, &r2))?;
+
+            (hex::encode(&state.salt), state.kdf)
         };
 
-        // Update user state
-        REGISTERED_USERS
-            .lock()
-            .unwrap()
-            .entry(user.clone())
-            .and_modify(|state| {
-                state.c = Some(challenge);
-                state.r1 = Some(r1);
-                state.r2 = Some(r2)
-            });
+        let reply = zkp_auth::AuthenticationChallengeResponse {
+            auth_id: auth_id.to_string(),
+            c: group.encode_scalar(&challenge),
+            salt,
+            kdf: Some(kdf.into()),
+        };
 
-        // map the auth_id to the user - override to always have the latest mapping
-        AUTH_ID_USER_MAP.lock().unwrap().insert(auth_id, user);
+        // every challenge has its own auth_id, so concurrent logins of a user do not clobber
+        // each other
+        pending_challenges.insert(
+            auth_id,
+            PendingChallenge {
+                user,
+                r1,
+                r2,
+                c: challenge,
+                issued_at,
+            },
+        );
 
         Ok(Response::new(reply))
     }
@@ -269,46 +475,813 @@ impl Auth for Verifier {
         info!("[Auth Server] Got an authentication answer request");
         debug!("[Auth Server] authentication answer payload: {request:?}");
 
+        let entry = self.pending_answer(&request.into_inner())?;
+
+        if self.zkp_verifier.verify(
+            &entry.s, &entry.c, &entry.y1, &entry.y2, &entry.r1, &entry.r2,
+        ) {
+            Ok(Response::new(zkp_auth::AuthenticationAnswerResponse {
+                session_id: gen_random_with_n_bits::<128>().to_string(),
+            }))
+        } else {
+            Err(AuthError::AuthenticationFailed.into())
+        }
+    }
+
+    /// Verify the authentication challenges of many ZKP Provers at once, with batch verification
+    /// (see [`zkp_core::Verifier::verify_batch`]). Each answer is authenticated (or not) on its
+    /// own, so an invalid answer does not fail the rest of the batch.
+    async fn verify_authentication_batch(
+        &self,
+        request: Request<AuthenticationAnswerBatchRequest>,
+    ) -> Result<Response<AuthenticationAnswerBatchResponse>, Status> {
+        info!("[Auth Server] Got an authentication answer batch request");
+        debug!("[Auth Server] authentication answer batch payload: {request:?}");
+
+        let answers = request.into_inner().answers;
+        let pending = answers
+            .iter()
+            .map(|answer| self.pending_answer(answer))
+            .collect::<Vec<_>>();
+
+        let entries = pending
+            .iter()
+            .filter_map(|entry| entry.as_ref().ok().cloned())
+            .collect::<Vec<_>>();
+        let mut verified = self.zkp_verifier.verify_batch(&entries).into_iter();
+
+        let results = answers
+            .into_iter()
+            .zip(pending)
+            .map(|(answer, entry)| {
+                let (authenticated, status) = match entry {
+                    Ok(_) if verified.next().unwrap() => (true, String::new()),
+                    Ok(_) => (false, AuthError::AuthenticationFailed.to_string()),
+                    Err(err) => (false, err.to_string()),
+                };
+
+                AuthenticationAnswerResult {
+                    auth_id: answer.auth_id,
+                    authenticated,
+                    session_id: if authenticated {
+                        gen_random_with_n_bits::<128>().to_string()
+                    } else {
+                        String::new()
+                    },
+                    status,
+                }
+            })
+            .collect();
+
+        Ok(Response::new(AuthenticationAnswerBatchResponse { results }))
+    }
+
+    /// Return the salt and the KDF parameters stored at the registration of the user, which the
+    /// Prover needs to derive `x` from the password for a non-interactive login
+    async fn get_login_parameters(
+        &self,
+        request: Request<LoginParametersRequest>,
+    ) -> Result<Response<LoginParametersResponse>, Status> {
+        info!("[Auth Server] Got a login parameters request");
+        debug!("[Auth Server] login parameters payload: {request:?}");
+
+        let user = request.into_inner().user;
+
+        match self.registered_users.lock().unwrap().get(&user) {
+            Some(state) => Ok(Response::new(LoginParametersResponse {
+                salt: hex::encode(&state.salt),
+                kdf: Some(state.kdf.into()),
+            })),
+            None => Err(AuthError::UnregisteredUser.into()),
+        }
+    }
+
+    /// Verify a non-interactive login proof, in a single step - the challenge is derived from the
+    /// login transcript, so no per-login state is kept
+    async fn login_with_proof(
+        &self,
+        request: Request<LoginWithProofRequest>,
+    ) -> Result<Response<AuthenticationAnswerResponse>, Status> {
+        info!("[Auth Server] Got a login with proof request");
+        debug!("[Auth Server] login with proof payload: {request:?}");
+
         let request = request.into_inner();
-        let (auth_id, s) = (
-            BigInt::parse_bytes(request.auth_id.as_bytes(), 10).unwrap(),
-            BigInt::parse_bytes(request.s.as_bytes(), 10).unwrap(),
+
+        let proof = Proof {
+            r1: self.decode_element("r1", &request.r1)?,
+            r2: self.decode_element("r2", &request.r2)?,
+            s: self.decode_scalar("s", &request.s)?,
+        };
+
+        if now().abs_diff(request.timestamp) > MAX_CLOCK_SKEW_SECS {
+            return Err(AuthError::StaleProof.into());
+        }
+
+        let (y1, y2) = match self.registered_users.lock().unwrap().get(&request.user) {
+            Some(state) => (state.y1.clone(), state.y2.clone()),
+            None => return Err(AuthError::UnregisteredUser.into()),
+        };
+
+        let transcript = Transcript::login(&self.server_id, &request.user, request.timestamp);
+
+        if !self.zkp_verifier.verify_proof(&y1, &y2, &proof, transcript) {
+            return Err(AuthError::AuthenticationFailed.into());
+        }
+
+        // the same commitment in a proof for another timestamp would reveal `x` (this also
+        // rejects the replay of a proof within its window)
+        if let Some(state) = self.registered_users.lock().unwrap().get_mut(&request.user) {
+            state
+                .recent_commitments
+                .insert(self.commitment_digest(&proof.r1, &proof.r2))?;
+        }
+
+        Ok(Response::new(zkp_auth::AuthenticationAnswerResponse {
+            session_id: gen_random_with_n_bits::<128>().to_string(),
+        }))
+    }
+
+    /// Report the group used by the server, and the result of its self-test
+    async fn get_status(
+        &self,
+        _request: Request<StatusRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        info!("[Auth Server] Got a status request");
+
+        let (self_test_passed, self_test_result) = match &self.self_test_result {
+            Some(Ok(())) => (true, "passed".to_string()),
+            Some(Err(err)) => (false, format!("failed: {err}")),
+            None => (false, "not run".to_string()),
+        };
+
+        Ok(Response::new(StatusResponse {
+            group: self.zkp_verifier.group().description(),
+            self_test_passed,
+            self_test_result,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+    use tonic::Code;
+    use zkp_core::{kdf, ModPGroup, Prover};
+
+    fn register_request<G: Group>(
+        prover: &Prover<G>,
+        user: &str,
+        x: &G::Scalar,
+    ) -> RegisterRequest {
+        let group = prover.group();
+        let (y1, y2) = prover.gen_public(x);
+
+        let salt = kdf::gen_salt();
+        let proof = prover.prove(x, Transcript::registration(DEFAULT_SERVER_ID, user, &salt));
+
+        RegisterRequest {
+            user: user.into(),
+            y1: group.encode_element(&y1),
+            y2: group.encode_element(&y2),
+            salt: hex::encode(salt),
+            kdf: Some(KdfParams::default().into()),
+            r1: group.encode_element(&proof.r1),
+            r2: group.encode_element(&proof.r2),
+            s: group.encode_scalar(&proof.s),
+        }
+    }
+
+    /// Make the commitments remembered for the user older by `secs` seconds
+    fn age_commitments(verifier: &Verifier<ModPGroup>, user: &str, secs: u64) {
+        let mut registered_users = verifier.registered_users.lock().unwrap();
+        let recent_commitments = &mut registered_users.get_mut(user).unwrap().recent_commitments;
+
+        for (seen_at, _) in &mut recent_commitments.seen_at {
+            *seen_at -= secs;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_with_proof() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let group = prover.group();
+
+        let x = group.random_scalar();
+
+        verifier
+            .register(Request::new(register_request(&prover, "Bob", &x)))
+            .await
+            .unwrap();
+
+        let login = |x, user: &str, timestamp| {
+            let proof = prover.prove(x, Transcript::login(DEFAULT_SERVER_ID, "Bob", timestamp));
+
+            verifier.login_with_proof(Request::new(LoginWithProofRequest {
+                user: user.into(),
+                r1: group.encode_element(&proof.r1),
+                r2: group.encode_element(&proof.r2),
+                s: group.encode_scalar(&proof.s),
+                timestamp,
+            }))
+        };
+
+        assert!(login(&x, "Bob", now()).await.is_ok());
+
+        let wrong_x = &x + 1u32;
+        let status = login(&wrong_x, "Bob", now()).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+
+        let status = login(&x, "Bob", now() - 2 * MAX_CLOCK_SKEW_SECS)
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+
+        let status = login(&x, "Alice", now()).await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_commitment_reuse() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let (group, x) = (prover.group(), prover.group().random_scalar());
+
+        verifier
+            .register(Request::new(register_request(&prover, "Bob", &x)))
+            .await
+            .unwrap();
+
+        let k = prover.gen_nonce();
+        let (r1, r2) = prover.gen_random(&k);
+
+        let challenge = || {
+            verifier.create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "Bob".into(),
+                r1: group.encode_element(&r1),
+                r2: group.encode_element(&r2),
+            }))
+        };
+
+        let response = challenge().await.unwrap().into_inner();
+        let c = group.decode_scalar(&response.c).unwrap();
+
+        verifier
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id: response.auth_id,
+                s: group.encode_scalar(&prover.challenge_answer(&c, &k, &x).unwrap()),
+            }))
+            .await
+            .unwrap();
+
+        // a second challenge for the same commitment is never issued
+        let status = challenge().await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(
+            status.message(),
+            "commitment (r1, r2) has already been used"
         );
 
-        // verifys: BigInt, c: BigInt, y1: BigInt, y2: BigInt, r1: BigInt, r2: BigInt
-        let user_for_auth_id = AUTH_ID_USER_MAP
-            .lock()
-            .unwrap()
-            .get(&auth_id)
-            .clone()
+        // nor is a login proof accepted twice
+        let proof = prover.prove(&x, Transcript::login(DEFAULT_SERVER_ID, "Bob", now()));
+        let login = || {
+            verifier.login_with_proof(Request::new(LoginWithProofRequest {
+                user: "Bob".into(),
+                r1: group.encode_element(&proof.r1),
+                r2: group.encode_element(&proof.r2),
+                s: group.encode_scalar(&proof.s),
+                timestamp: now(),
+            }))
+        };
+
+        assert!(login().await.is_ok());
+        assert_eq!(login().await.unwrap_err().code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_answer_consumes_challenge() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let (group, x) = (prover.group(), prover.group().random_scalar());
+
+        verifier
+            .register(Request::new(register_request(&prover, "Bob", &x)))
+            .await
+            .unwrap();
+
+        // two concurrent logins of the same user
+        let mut answers = Vec::new();
+        for _ in 0..2 {
+            let k = prover.gen_nonce();
+            let (r1, r2) = prover.gen_random(&k);
+
+            let challenge = verifier
+                .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                    user: "Bob".into(),
+                    r1: group.encode_element(&r1),
+                    r2: group.encode_element(&r2),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            let c = group.decode_scalar(&challenge.c).unwrap();
+
+            answers.push(AuthenticationAnswerRequest {
+                auth_id: challenge.auth_id,
+                s: group.encode_scalar(&prover.challenge_answer(&c, &k, &x).unwrap()),
+            });
+        }
+
+        // the first challenge is still answered after the second has been issued, but an answer
+        // is only accepted once
+        for answer in &answers {
+            let verify = || verifier.verify_authentication(Request::new(answer.clone()));
+
+            assert!(verify().await.is_ok());
+
+            let status = verify().await.unwrap_err();
+            assert_eq!(status.code(), Code::NotFound);
+            assert_eq!(status.message(), "unknown auth_id");
+        }
+
+        let results = verifier
+            .verify_authentication_batch(Request::new(AuthenticationAnswerBatchRequest { answers }))
+            .await
             .unwrap()
-            .clone();
+            .into_inner()
+            .results;
+        assert!(results.iter().all(|result| !result.authenticated));
+        let pending_challenges = verifier.pending_challenges.lock().unwrap();
+        assert!(pending_challenges.by_auth_id.is_empty());
+        assert!(pending_challenges.by_user.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pending_challenges_are_bounded() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let (group, x) = (prover.group(), prover.group().random_scalar());
+
+        verifier
+            .register(Request::new(register_request(&prover, "Bob", &x)))
+            .await
+            .unwrap();
+
+        let challenge = || {
+            let (r1, r2) = prover.gen_random(&prover.gen_nonce());
+
+            verifier.create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "Bob".into(),
+                r1: group.encode_element(&r1),
+                r2: group.encode_element(&r2),
+            }))
+        };
+
+        let mut auth_ids = Vec::new();
+        for _ in 0..MAX_PENDING_CHALLENGES {
+            auth_ids.push(challenge().await.unwrap().into_inner().auth_id);
+        }
+
+        let status = challenge().await.unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
 
-        let user_state = REGISTERED_USERS
+        // the challenges expire if they are not answered
+        for pending in verifier
+            .pending_challenges
             .lock()
             .unwrap()
-            .get(&user_for_auth_id)
-            .clone()
+            .by_auth_id
+            .values_mut()
+        {
+            pending.issued_at -= CHALLENGE_TTL_SECS + 1;
+        }
+
+        let status = verifier
+            .verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id: auth_ids[0].clone(),
+                s: "1".into(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+        assert_eq!(status.message(), "challenge has expired");
+
+        // and are dropped when the next challenge is issued
+        assert!(challenge().await.is_ok());
+        let pending_challenges = verifier.pending_challenges.lock().unwrap();
+        assert_eq!(pending_challenges.by_auth_id.len(), 1);
+        assert_eq!(pending_challenges.by_user["Bob"].len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_replay_after_many_logins() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let (group, x) = (prover.group(), prover.group().random_scalar());
+
+        verifier
+            .register(Request::new(register_request(&prover, "Bob", &x)))
+            .await
+            .unwrap();
+
+        let login = |proof: &Proof<ModPGroup>, timestamp| {
+            verifier.login_with_proof(Request::new(LoginWithProofRequest {
+                user: "Bob".into(),
+                r1: group.encode_element(&proof.r1),
+                r2: group.encode_element(&proof.r2),
+                s: group.encode_scalar(&proof.s),
+                timestamp,
+            }))
+        };
+
+        let timestamp = now();
+        let captured = prover.prove(&x, Transcript::login(DEFAULT_SERVER_ID, "Bob", timestamp));
+        assert!(login(&captured, timestamp).await.is_ok());
+
+        // no number of logins flushes the captured proof out of the replay cache
+        for _ in 0..300 {
+            let proof = prover.prove(&x, Transcript::login(DEFAULT_SERVER_ID, "Bob", timestamp));
+            assert!(login(&proof, timestamp).await.is_ok());
+        }
+
+        let status = login(&captured, timestamp).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        // the commitments are only forgotten once they are older than their time to live
+        age_commitments(&verifier, "Bob", COMMITMENT_TTL_SECS + 1);
+
+        let proof = prover.prove(&x, Transcript::login(DEFAULT_SERVER_ID, "Bob", timestamp));
+        assert!(login(&proof, timestamp).await.is_ok());
+        assert_eq!(
+            verifier.registered_users.lock().unwrap()["Bob"]
+                .recent_commitments
+                .digests
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_commitment_reuse_after_challenge_window() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let (group, x) = (prover.group(), prover.group().random_scalar());
+
+        verifier
+            .register(Request::new(register_request(&prover, "Bob", &x)))
+            .await
+            .unwrap();
+
+        let (r1, r2) = prover.gen_random(&prover.gen_nonce());
+        let challenge = || {
+            verifier.create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "Bob".into(),
+                r1: group.encode_element(&r1),
+                r2: group.encode_element(&r2),
+            }))
+        };
+
+        assert!(challenge().await.is_ok());
+
+        // a client repeating its nonce in a login after the first challenge has expired (and
+        // after any login proof with it would be stale) is still refused a second challenge
+        age_commitments(
+            &verifier,
+            "Bob",
+            CHALLENGE_TTL_SECS + MAX_CLOCK_SKEW_SECS + 1,
+        );
+
+        let status = challenge().await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(
+            status.message(),
+            "commitment (r1, r2) has already been used"
+        );
+
+        age_commitments(&verifier, "Bob", COMMITMENT_TTL_SECS + 1);
+        assert!(challenge().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_authentication_batch() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let group = prover.group();
+
+        let mut answers = Vec::new();
+
+        for user in ["Alice", "Bob", "Carol"] {
+            let x = group.random_scalar();
+
+            verifier
+                .register(Request::new(register_request(&prover, user, &x)))
+                .await
+                .unwrap();
+
+            let k = prover.gen_nonce();
+            let (r1, r2) = prover.gen_random(&k);
+
+            let challenge = verifier
+                .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                    user: user.into(),
+                    r1: group.encode_element(&r1),
+                    r2: group.encode_element(&r2),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+
+            // Bob answers with the wrong secret
+            let x = if user == "Bob" { &x + 1u32 } else { x };
+            let c = group.decode_scalar(&challenge.c).unwrap();
+
+            answers.push(AuthenticationAnswerRequest {
+                auth_id: challenge.auth_id,
+                s: group.encode_scalar(&prover.challenge_answer(&c, &k, &x).unwrap()),
+            });
+        }
+
+        answers.push(AuthenticationAnswerRequest {
+            auth_id: "42".into(),
+            s: "1".into(),
+        });
+
+        let results = verifier
+            .verify_authentication_batch(Request::new(AuthenticationAnswerBatchRequest { answers }))
+            .await
             .unwrap()
-            .clone();
-
-        let (y1, y2, r1, r2, c) = (
-            user_state.y1.clone(),
-            user_state.y2.clone(),
-            user_state.r1.clone().unwrap().clone(),
-            user_state.r2.clone().unwrap().clone(),
-            user_state.c.clone().unwrap().clone(),
+            .into_inner()
+            .results;
+
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| result.authenticated)
+                .collect::<Vec<_>>(),
+            vec![true, false, true, false]
         );
+        assert!(!results[0].session_id.is_empty());
+        assert_eq!(results[3].status, "unknown auth_id");
+    }
 
-        if zkp_verifier::verify(&s, &c, &y1, &y2, &r1, &r2) {
-            Ok(Response::new(zkp_auth::AuthenticationAnswerResponse {
-                session_id: zkp_verifier::gen_random_with_n_bits::<128>().to_string(),
+    #[tokio::test]
+    async fn test_register() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let (group, x) = (prover.group(), prover.group().random_scalar());
+
+        let request = register_request(&prover, "Bob", &x);
+        verifier
+            .register(Request::new(request.clone()))
+            .await
+            .unwrap();
+
+        // the salt and the KDF parameters are returned at challenge time...
+        let challenge = verifier
+            .create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: "Bob".into(),
+                r1: group.encode_element(group.g()),
+                r2: group.encode_element(group.h()),
             }))
-        } else {
-            Err(Status::new(
-                Code::Unauthenticated,
-                "user authentication failed",
-            ))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(challenge.salt, request.salt);
+        assert_eq!(challenge.kdf, request.kdf);
+
+        // and by GetLoginParameters, for the non-interactive login
+        let parameters = verifier
+            .get_login_parameters(Request::new(LoginParametersRequest { user: "Bob".into() }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(parameters.salt, request.salt);
+        assert_eq!(parameters.kdf, request.kdf);
+
+        let status = verifier
+            .get_login_parameters(Request::new(LoginParametersRequest {
+                user: "Alice".into(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        let invalid = [
+            RegisterRequest {
+                salt: "00ff".into(),
+                ..register_request(&prover, "Alice", &x)
+            },
+            RegisterRequest {
+                kdf: None,
+                ..register_request(&prover, "Alice", &x)
+            },
+            RegisterRequest {
+                kdf: Some(zkp_auth::KdfParams {
+                    memory_kib: 0,
+                    iterations: 0,
+                    parallelism: 0,
+                }),
+                ..register_request(&prover, "Alice", &x)
+            },
+        ];
+
+        for request in invalid {
+            let status = verifier.register(Request::new(request)).await.unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_proof() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let (group, x) = (prover.group(), prover.group().random_scalar());
+
+        let invalid = [
+            // log_g(y1) != log_h(y2)
+            RegisterRequest {
+                y2: group.encode_element(&group.exp(group.h(), &(&x + 1u32))),
+                ..register_request(&prover, "Bob", &x)
+            },
+            // a proof for someone else's keys
+            RegisterRequest {
+                y1: group.encode_element(group.g()),
+                y2: group.encode_element(group.h()),
+                ..register_request(&prover, "Bob", &x)
+            },
+            // a proof for the registration of another user
+            RegisterRequest {
+                user: "Bob".into(),
+                ..register_request(&prover, "Alice", &x)
+            },
+            // a proof for another salt
+            RegisterRequest {
+                salt: hex::encode(kdf::gen_salt()),
+                ..register_request(&prover, "Bob", &x)
+            },
+        ];
+
+        for request in invalid {
+            let status = verifier.register(Request::new(request)).await.unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+            assert_eq!(status.message(), "registration proof did not verify");
+        }
+
+        assert!(verifier.registered_users.lock().unwrap().is_empty());
+
+        verifier
+            .register(Request::new(register_request(&prover, "Bob", &x)))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_register_invalid_public_data() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let group = prover.group();
+        let (g, p) = (group.g().clone(), group.p().clone());
+
+        for (y1, y2, reason) in [
+            (BigUint::from(0u32), g.clone(), "not in the range [1, p)"),
+            (g.clone(), p.clone(), "not in the range [1, p)"),
+            (BigUint::from(1u32), BigUint::from(1u32), "the identity"),
+            (g.clone(), &p - 1u32, "not in the subgroup of order q"),
+        ] {
+            let status = verifier
+                .register(Request::new(RegisterRequest {
+                    y1: group.encode_element(&y1),
+                    y2: group.encode_element(&y2),
+                    ..register_request(&prover, "Bob", &BigUint::from(1u32))
+                }))
+                .await
+                .unwrap_err();
+
+            assert_eq!(status.code(), Code::InvalidArgument);
+            assert!(status.message().ends_with(reason), "{}", status.message());
+        }
+
+        assert!(verifier.registered_users.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_requests() {
+        let verifier = Verifier::<ModPGroup>::default();
+        let prover = Prover::new(ModPGroup::default());
+        let group = prover.group();
+        let (g, h, q) = (group.g(), group.h(), group.q());
+
+        verifier
+            .register(Request::new(register_request(
+                &prover,
+                "Bob",
+                &BigUint::from(1u32),
+            )))
+            .await
+            .unwrap();
+
+        let challenge = |user: &str, r1: String, r2: String| {
+            verifier.create_authentication_challenge(Request::new(AuthenticationChallengeRequest {
+                user: user.into(),
+                r1,
+                r2,
+            }))
+        };
+
+        fn code<T>(result: Result<T, Status>) -> Code {
+            result.map(|_| ()).unwrap_err().code()
         }
+
+        let encoded = group.encode_element(g);
+        assert_eq!(
+            code(challenge("Bob", "forty-two".into(), encoded.clone()).await),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            code(challenge("Bob", encoded.clone(), "1".into()).await),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            code(
+                challenge(
+                    "Bob",
+                    encoded.clone(),
+                    group.encode_element(&(group.p() - 1u32))
+                )
+                .await
+            ),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            code(challenge("Alice", encoded.clone(), encoded.clone()).await),
+            Code::NotFound
+        );
+
+        let auth_id = challenge("Bob", encoded.clone(), encoded)
+            .await
+            .unwrap()
+            .into_inner()
+            .auth_id;
+
+        let answer = |auth_id: &str, s: String| {
+            verifier.verify_authentication(Request::new(AuthenticationAnswerRequest {
+                auth_id: auth_id.into(),
+                s,
+            }))
+        };
+
+        assert_eq!(
+            code(answer("not a number", "1".into()).await),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            code(answer(&auth_id, "-1".into()).await),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            code(answer(&auth_id, group.encode_scalar(q)).await),
+            Code::InvalidArgument
+        );
+        assert_eq!(code(answer("42", "1".into()).await), Code::NotFound);
+        assert_eq!(
+            code(answer(&auth_id, "1".into()).await),
+            Code::Unauthenticated
+        );
+
+        let status = verifier
+            .login_with_proof(Request::new(LoginWithProofRequest {
+                user: "Bob".into(),
+                r1: group.encode_element(g),
+                r2: group.encode_element(h),
+                s: group.encode_scalar(&(q + 1u32)),
+                timestamp: now(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), "s is not reduced modulo the group order");
+    }
+
+    #[tokio::test]
+    async fn test_get_status() {
+        let mut verifier = Verifier::<ModPGroup>::default();
+
+        let status = verifier
+            .get_status(Request::new(StatusRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!status.self_test_passed);
+
+        verifier.self_test().unwrap();
+
+        let status = verifier
+            .get_status(Request::new(StatusRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(status.self_test_passed);
+        assert_eq!(status.group, "modp (255-bit p, 236-bit q)");
     }
 }
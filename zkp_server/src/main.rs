@@ -1,27 +1,43 @@
+use std::net::SocketAddr;
+
 use tonic::transport::Server;
 use tracing::info;
-use tracing_subscriber;
 
+use zkp_core::{params, redact, transcript, Flavor, Group, Ristretto255Group};
 use zkp_server::{zkp_auth::auth_server::AuthServer, Verifier};
 
+/// Serve the Auth requests with a ZKP Verifier for the given group, provided that its
+/// parameters pass the self-test
+async fn serve<G: Group>(group: G, address: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+    let mut verifier = Verifier::new(group).with_server_id(transcript::server_id_from_env());
+    verifier.self_test()?;
+
+    Server::builder()
+        .add_service(AuthServer::new(verifier))
+        .serve(address)
+        .await?;
+
+    Ok(())
+}
+
 /// The entryppint for the ZKP Auth Server
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt::init();
+    redact::init();
 
     // In production, these would be read off of configuration files.
-    const SERVER_ADDR: &'static str = "0.0.0.0";
-    const SERVER_PORT: &'static str = "9999";
+    const SERVER_ADDR: &str = "0.0.0.0";
+    const SERVER_PORT: &str = "9999";
 
     let address = format!("{SERVER_ADDR}:{SERVER_PORT}").parse()?;
-    let verifier = Verifier::default();
+    let flavor = Flavor::from_env()?;
 
-    info!("Started ZKP Server on port {SERVER_PORT}");
+    info!("Started ZKP Server on port {SERVER_PORT} ({flavor:?} flavor)");
 
-    Server::builder()
-        .add_service(AuthServer::new(verifier))
-        .serve(address)
-        .await?;
+    match flavor {
+        Flavor::ModP => serve(params::group_from_env()?, address).await?,
+        Flavor::Ristretto255 => serve(Ristretto255Group::default(), address).await?,
+    }
 
     Ok(())
 }